@@ -0,0 +1,38 @@
+use soroban_sdk::{Address, Env, Vec};
+use vesting_contracts::BatchCreateData;
+
+use crate::Grant;
+
+/// Rows per `BatchCreateData` unless told otherwise. Each vault adds several
+/// entries to the contract's storage, and one transaction may only write so
+/// much.
+pub const DEFAULT_CHUNK_SIZE: usize = 20;
+
+/// Split `grants` into consecutive chunks of at most `chunk_size` rows, one
+/// per transaction.
+pub fn chunks(grants: &[Grant], chunk_size: usize) -> std::vec::Vec<&[Grant]> {
+    grants.chunks(chunk_size.max(1)).collect()
+}
+
+/// The `batch_create_vaults_*` argument for one chunk.
+pub fn batch_data(env: &Env, grants: &[Grant]) -> BatchCreateData {
+    let mut batch = BatchCreateData {
+        recipients: Vec::new(env),
+        amounts: Vec::new(env),
+        start_times: Vec::new(env),
+        end_times: Vec::new(env),
+        keeper_fees: Vec::new(env),
+        step_durations: Vec::new(env),
+    };
+    for grant in grants {
+        batch
+            .recipients
+            .push_back(Address::from_str(env, &grant.recipient));
+        batch.amounts.push_back(grant.amount);
+        batch.start_times.push_back(grant.start_time);
+        batch.end_times.push_back(grant.end_time);
+        batch.keeper_fees.push_back(grant.keeper_fee);
+        batch.step_durations.push_back(grant.step_duration);
+    }
+    batch
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("row {row}: {message}")]
+    Parse { row: usize, message: String },
+
+    #[error("{} invalid row(s):\n{}", .0.len(), RowErrors(.0))]
+    Invalid(Vec<RowError>),
+}
+
+/// A problem with one cell of the cap table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowError {
+    /// CSV: line the record starts on. JSON: 1-based index in the array.
+    pub row: usize,
+    pub column: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "row {}, {}: {}", self.row, self.column, self.message)
+    }
+}
+
+struct RowErrors<'a>(&'a [RowError]);
+
+impl fmt::Display for RowErrors<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  {error}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use chrono::{DateTime, NaiveDate};
+use soroban_sdk::xdr::ScAddress;
+
+use crate::{Error, RawRow, RowError};
+
+/// Longest title `set_vault_title` accepts, in bytes
+pub const MAX_TITLE_LEN: usize = 32;
+
+/// One validated cap-table row, ready to become a vault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grant {
+    pub row: usize,
+    pub recipient: String,
+    pub amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_duration: u64,
+    pub keeper_fee: i128,
+    pub title: Option<String>,
+}
+
+/// Validate every row, reporting all problems at once rather than the first.
+///
+/// Columns are `address`, `amount`, `start` and `end`, plus optional
+/// `cliff`, `step`, `keeper_fee` and `title`; anything else is ignored.
+/// Times are unix seconds, `YYYY-MM-DD` (midnight UTC) or RFC 3339.
+/// Durations are seconds or a number with an `s`, `m`, `h`, `d` or `w`
+/// suffix.
+pub fn validate(rows: &[RawRow]) -> Result<Vec<Grant>, Error> {
+    let mut grants = Vec::new();
+    let mut errors = Vec::new();
+    for row in rows {
+        match validate_row(row) {
+            Ok(grant) => grants.push(grant),
+            Err(row_errors) => errors.extend(row_errors),
+        }
+    }
+    if errors.is_empty() {
+        Ok(grants)
+    } else {
+        Err(Error::Invalid(errors))
+    }
+}
+
+fn validate_row(row: &RawRow) -> Result<Grant, Vec<RowError>> {
+    let mut errors = Vec::new();
+
+    let recipient = check(
+        &mut errors,
+        row.row,
+        "address",
+        required(row, "address").and_then(address),
+    );
+    let amount = check(
+        &mut errors,
+        row.row,
+        "amount",
+        required(row, "amount").and_then(amount),
+    );
+    let start_time = check(
+        &mut errors,
+        row.row,
+        "start",
+        required(row, "start").and_then(time),
+    );
+    let end_time = check(
+        &mut errors,
+        row.row,
+        "end",
+        required(row, "end").and_then(time),
+    );
+    let cliff = check(
+        &mut errors,
+        row.row,
+        "cliff",
+        optional(row, "cliff", duration),
+    );
+    let step_duration = check(
+        &mut errors,
+        row.row,
+        "step",
+        optional(row, "step", duration),
+    );
+    let keeper_fee = check(
+        &mut errors,
+        row.row,
+        "keeper_fee",
+        optional(row, "keeper_fee", keeper_fee),
+    );
+    let title = check(&mut errors, row.row, "title", title(row.cell("title")));
+
+    if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+        if end_time <= start_time {
+            errors.push(row.error("end", "must be after start"));
+        } else if let Some(step) = step_duration {
+            // Batch creation only accepts whole steps
+            if step > 0 && !(end_time - start_time).is_multiple_of(step) {
+                errors.push(row.error("step", "must divide the vesting period"));
+            }
+        }
+    }
+    if let (Some(amount), Some(fee)) = (amount, keeper_fee) {
+        if fee > amount {
+            errors.push(row.error("keeper_fee", "must not exceed amount"));
+        }
+    }
+    // Vesting begins at start, so the only cliff a vault can express is its
+    // first step
+    if let (Some(cliff), Some(step)) = (cliff, step_duration) {
+        if cliff != 0 && cliff != step {
+            errors.push(row.error(
+                "cliff",
+                "must be 0 or equal to step; vaults vest from start, so a cliff is \
+                 their first step",
+            ));
+        }
+    }
+
+    match (
+        recipient,
+        amount,
+        start_time,
+        end_time,
+        step_duration,
+        keeper_fee,
+        title,
+    ) {
+        (
+            Some(recipient),
+            Some(amount),
+            Some(start_time),
+            Some(end_time),
+            Some(step_duration),
+            Some(keeper_fee),
+            Some(title),
+        ) if errors.is_empty() => Ok(Grant {
+            row: row.row,
+            recipient,
+            amount,
+            start_time,
+            end_time,
+            step_duration,
+            keeper_fee,
+            title,
+        }),
+        _ => Err(errors),
+    }
+}
+
+// Record a failed cell, keeping the value of a good one
+fn check<T>(
+    errors: &mut Vec<RowError>,
+    row: usize,
+    column: &'static str,
+    result: Result<T, String>,
+) -> Option<T> {
+    result
+        .map_err(|message| {
+            errors.push(RowError {
+                row,
+                column,
+                message,
+            })
+        })
+        .ok()
+}
+
+impl RawRow {
+    fn error(&self, column: &'static str, message: &str) -> RowError {
+        RowError {
+            row: self.row,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn required<'a>(row: &'a RawRow, column: &str) -> Result<&'a str, String> {
+    row.cell(column).ok_or_else(|| "missing".to_string())
+}
+
+fn optional<T: Default>(
+    row: &RawRow,
+    column: &str,
+    parse: fn(&str) -> Result<T, String>,
+) -> Result<T, String> {
+    row.cell(column).map_or(Ok(T::default()), parse)
+}
+
+fn address(text: &str) -> Result<String, String> {
+    text.parse::<ScAddress>()
+        .map(|_| text.to_string())
+        .map_err(|_| format!("{text} is not a Stellar address"))
+}
+
+fn amount(text: &str) -> Result<i128, String> {
+    match text.parse::<i128>() {
+        Ok(amount) if amount > 0 => Ok(amount),
+        Ok(_) => Err("must be positive".into()),
+        Err(_) => Err(format!("{text} is not a whole number of token units")),
+    }
+}
+
+fn keeper_fee(text: &str) -> Result<i128, String> {
+    match text.parse::<i128>() {
+        Ok(fee) if fee >= 0 => Ok(fee),
+        Ok(_) => Err("must not be negative".into()),
+        Err(_) => Err(format!("{text} is not a whole number of token units")),
+    }
+}
+
+fn time(text: &str) -> Result<u64, String> {
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Ok(seconds);
+    }
+    let seconds = if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0)
+            .expect("midnight")
+            .and_utc()
+            .timestamp()
+    } else if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        time.timestamp()
+    } else {
+        return Err(format!("{text} is not a unix time, date or RFC 3339 time"));
+    };
+    u64::try_from(seconds).map_err(|_| format!("{text} is before 1970"))
+}
+
+fn duration(text: &str) -> Result<u64, String> {
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => text.split_at(index),
+        None => (text, "s"),
+    };
+    let scale = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("{text} is not a duration like 90d")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(scale))
+        .ok_or_else(|| format!("{text} is not a duration like 90d"))
+}
+
+fn title(text: Option<&str>) -> Result<Option<String>, String> {
+    match text {
+        Some(title) if title.len() > MAX_TITLE_LEN => {
+            Err(format!("longer than {MAX_TITLE_LEN} bytes"))
+        }
+        _ => Ok(text.map(str::to_string)),
+    }
+}
@@ -0,0 +1,32 @@
+//! Cap-table import for `VestingContract`.
+//!
+//! HR spreadsheets, exported as CSV or JSON, are [parsed](parse_csv) into raw
+//! rows, [validated](validate) into [`Grant`]s with every problem reported
+//! at once, and split into [chunks] small enough for one transaction each,
+//! whose [`batch_data`] is the `BatchCreateData` to submit. After creation,
+//! [`reconcile`] checks the resulting vaults against the table.
+
+mod batch;
+mod error;
+mod grant;
+mod parse;
+mod reconcile;
+
+pub use batch::{batch_data, chunks, DEFAULT_CHUNK_SIZE};
+pub use error::{Error, RowError};
+pub use grant::{validate, Grant, MAX_TITLE_LEN};
+pub use parse::{parse_csv, parse_json, RawRow};
+pub use reconcile::{reconcile, Report, ReportLine, Status};
+
+use std::path::Path;
+
+/// Read and validate a cap table, choosing the format by extension (`.json`,
+/// otherwise CSV).
+pub fn load(path: &Path) -> Result<Vec<Grant>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    let rows = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => parse_json(&text)?,
+        _ => parse_csv(&text)?,
+    };
+    validate(&rows)
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::Error;
+
+/// One cap-table record before validation: cell text keyed by lowercase
+/// column name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawRow {
+    pub row: usize,
+    pub cells: BTreeMap<String, String>,
+}
+
+impl RawRow {
+    /// Trimmed cell text; missing and blank cells are both `None`.
+    pub fn cell(&self, column: &str) -> Option<&str> {
+        self.cells
+            .get(column)
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+    }
+}
+
+/// Parse CSV with a header row. Fields may be quoted, with `""` for a quote
+/// inside a quoted field; blank lines are skipped.
+pub fn parse_csv(text: &str) -> Result<Vec<RawRow>, Error> {
+    let mut records = csv_records(text)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+
+    records
+        .map(|(row, fields)| {
+            if fields.len() != header.len() {
+                return Err(Error::Parse {
+                    row,
+                    message: format!("expected {} fields, found {}", header.len(), fields.len()),
+                });
+            }
+            Ok(RawRow {
+                row,
+                cells: header.iter().cloned().zip(fields).collect(),
+            })
+        })
+        .collect()
+}
+
+// Split into records of fields, each tagged with the line it starts on
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, Error> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|field| !field.trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        return Err(Error::Parse {
+            row: start,
+            message: "unterminated quoted field".into(),
+        });
+    }
+    fields.push(field);
+    if fields.iter().any(|field| !field.trim().is_empty()) {
+        records.push((start, fields));
+    }
+    Ok(records)
+}
+
+/// Parse a JSON array of objects. Numbers and strings are both accepted as
+/// cell values, and `null` counts as blank. Amounts that do not fit in 64
+/// bits must be strings, since JSON numbers that large lose precision.
+pub fn parse_json(text: &str) -> Result<Vec<RawRow>, Error> {
+    let value: Value = serde_json::from_str(text).map_err(|e| Error::Parse {
+        row: e.line(),
+        message: e.to_string(),
+    })?;
+    let Value::Array(items) = value else {
+        return Err(Error::Parse {
+            row: 0,
+            message: "expected an array of objects".into(),
+        });
+    };
+
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let row = index + 1;
+            let Value::Object(object) = item else {
+                return Err(Error::Parse {
+                    row,
+                    message: "expected an object".into(),
+                });
+            };
+            let mut cells = BTreeMap::new();
+            for (key, value) in object {
+                let text = match value {
+                    Value::Null => String::new(),
+                    Value::String(text) => text,
+                    Value::Number(number) => number.to_string(),
+                    other => {
+                        return Err(Error::Parse {
+                            row,
+                            message: format!("{key}: expected a string or number, found {other}"),
+                        })
+                    }
+                };
+                cells.insert(key.to_lowercase(), text);
+            }
+            Ok(RawRow { row, cells })
+        })
+        .collect()
+}
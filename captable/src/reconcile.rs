@@ -0,0 +1,126 @@
+use std::fmt;
+
+use soroban_sdk::xdr::ScAddress;
+use vesting_contracts::Vault;
+
+use crate::Grant;
+
+/// How a created vault compares with its cap-table row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Matched,
+    /// Fields that differ from the row
+    Mismatched(Vec<&'static str>),
+    Missing,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportLine {
+    pub row: usize,
+    pub vault_id: u64,
+    pub recipient: String,
+    pub amount: i128,
+    pub status: Status,
+}
+
+/// Row-by-row comparison of a cap table with the vaults created from it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub lines: Vec<ReportLine>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        self.lines.iter().all(|line| line.status == Status::Matched)
+    }
+
+    fn count(&self, matches: fn(&Status) -> bool) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| matches(&line.status))
+            .count()
+    }
+}
+
+/// Compare `grants` with the vaults created for them. Batches assign
+/// consecutive ids, so row `n` is expected at `first_vault_id + n`;
+/// `lookup` returns the vault stored under an id, if any.
+pub fn reconcile(
+    grants: &[Grant],
+    first_vault_id: u64,
+    lookup: impl Fn(u64) -> Option<Vault>,
+) -> Report {
+    let lines = grants
+        .iter()
+        .zip(first_vault_id..)
+        .map(|(grant, vault_id)| ReportLine {
+            row: grant.row,
+            vault_id,
+            recipient: grant.recipient.clone(),
+            amount: grant.amount,
+            status: match lookup(vault_id) {
+                Some(vault) => compare(grant, &vault),
+                None => Status::Missing,
+            },
+        })
+        .collect();
+    Report { lines }
+}
+
+fn compare(grant: &Grant, vault: &Vault) -> Status {
+    let mut fields = Vec::new();
+    if ScAddress::from(&vault.owner).to_string() != grant.recipient {
+        fields.push("address");
+    }
+    if vault.total_amount != grant.amount {
+        fields.push("amount");
+    }
+    if vault.start_time != grant.start_time {
+        fields.push("start");
+    }
+    if vault.end_time != grant.end_time {
+        fields.push("end");
+    }
+    if vault.step_duration != grant.step_duration {
+        fields.push("step");
+    }
+    if vault.keeper_fee != grant.keeper_fee {
+        fields.push("keeper_fee");
+    }
+    if vault.title.to_string() != grant.title.clone().unwrap_or_default() {
+        fields.push("title");
+    }
+    if fields.is_empty() {
+        Status::Matched
+    } else {
+        Status::Mismatched(fields)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "row\tvault\trecipient\tamount\tstatus")?;
+        for line in &self.lines {
+            let status = match &line.status {
+                Status::Matched => "ok".to_string(),
+                Status::Mismatched(fields) => format!("mismatch: {}", fields.join(", ")),
+                Status::Missing => "missing".to_string(),
+            };
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}",
+                line.row, line.vault_id, line.recipient, line.amount, status
+            )?;
+        }
+        let planned: i128 = self.lines.iter().map(|line| line.amount).sum();
+        write!(
+            f,
+            "{} rows, {} ok, {} mismatched, {} missing; {} tokens planned",
+            self.lines.len(),
+            self.count(|status| *status == Status::Matched),
+            self.count(|status| matches!(status, Status::Mismatched(_))),
+            self.count(|status| *status == Status::Missing),
+            planned
+        )
+    }
+}
@@ -0,0 +1,161 @@
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::xdr::ScAddress;
+use soroban_sdk::{Address, Env};
+use vesting_captable::{
+    batch_data, chunks, parse_csv, parse_json, reconcile, validate, Error, Grant, RowError, Status,
+};
+use vesting_contracts::{VestingContract, VestingContractClient};
+
+fn strkey(address: &Address) -> String {
+    ScAddress::from(address).to_string()
+}
+
+fn grant(recipient: &str, amount: i128) -> Grant {
+    Grant {
+        row: 0,
+        recipient: recipient.to_string(),
+        amount,
+        start_time: 1_000,
+        end_time: 2_000,
+        step_duration: 0,
+        keeper_fee: 0,
+        title: None,
+    }
+}
+
+#[test]
+fn test_csv_rows() {
+    let text = "Name,Address,Amount,Start,End,Title\r\n\
+                \"Doe, Jane\",GA1,1000,0,100,\"The \"\"A\"\" team\"\r\n\
+                \r\n\
+                \"Multi\nline\",GA2,5,0,100,\n";
+    let rows = parse_csv(text).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].row, 2);
+    assert_eq!(rows[0].cell("name"), Some("Doe, Jane"));
+    assert_eq!(rows[0].cell("title"), Some("The \"A\" team"));
+    assert_eq!(rows[1].row, 4);
+    assert_eq!(rows[1].cell("address"), Some("GA2"));
+    assert_eq!(rows[1].cell("title"), None);
+
+    assert!(matches!(
+        parse_csv("address,amount\nGA1,1,2\n"),
+        Err(Error::Parse { row: 2, .. })
+    ));
+    assert!(matches!(
+        parse_csv("address,amount\n\"GA1,1\n"),
+        Err(Error::Parse { row: 2, .. })
+    ));
+}
+
+#[test]
+fn test_validation() {
+    let env = Env::default();
+    let alice = strkey(&Address::generate(&env));
+    let text = format!(
+        r#"[
+            {{"address": "{alice}", "amount": 1200, "start": "2025-01-01",
+              "end": "2025-12-27T00:00:00Z", "cliff": "30d", "step": "30d",
+              "keeper_fee": 5, "title": "Seed"}},
+            {{"address": "nobody", "amount": 0, "start": 200, "end": 100}},
+            {{"address": "{alice}", "amount": "1", "start": 0, "end": 100,
+              "cliff": 10, "step": 30, "keeper_fee": 2, "title": "{long}"}}
+        ]"#,
+        long = "x".repeat(33)
+    );
+    let rows = parse_json(&text).unwrap();
+
+    let Err(Error::Invalid(errors)) = validate(&rows) else {
+        panic!("expected row errors");
+    };
+    let problems: Vec<(usize, &str)> = errors
+        .iter()
+        .map(|RowError { row, column, .. }| (*row, *column))
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            (2, "address"),
+            (2, "amount"),
+            (2, "end"),
+            (3, "title"),
+            (3, "step"),
+            (3, "keeper_fee"),
+            (3, "cliff")
+        ]
+    );
+
+    assert_eq!(
+        validate(&rows[..1]).unwrap(),
+        [Grant {
+            row: 1,
+            recipient: alice,
+            amount: 1200,
+            start_time: 1_735_689_600,
+            end_time: 1_766_793_600,
+            step_duration: 30 * 86_400,
+            keeper_fee: 5,
+            title: Some("Seed".into()),
+        }]
+    );
+}
+
+#[test]
+fn test_chunks() {
+    let env = Env::default();
+    let recipient = strkey(&Address::generate(&env));
+    let grants: Vec<Grant> = (1..=45).map(|amount| grant(&recipient, amount)).collect();
+
+    let sizes: Vec<usize> = chunks(&grants, 20)
+        .iter()
+        .map(|chunk| chunk.len())
+        .collect();
+    assert_eq!(sizes, [20, 20, 5]);
+
+    let batch = batch_data(&env, chunks(&grants, 20)[2]);
+    assert_eq!(batch.amounts.len(), 5);
+    assert_eq!(batch.amounts.get(0), Some(41));
+    assert_eq!(strkey(&batch.recipients.get(4).unwrap()), recipient);
+}
+
+#[test]
+fn test_reconcile() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(VestingContract, ());
+    let client = VestingContractClient::new(&env, &contract_id);
+    client.initialize(&contract_id, &1_000_000i128);
+
+    let alice = strkey(&Address::generate(&env));
+    let bob = strkey(&Address::generate(&env));
+    let grants = [grant(&alice, 1000), grant(&bob, 500)];
+    client.batch_create_vaults_full(&batch_data(&env, &grants));
+
+    let report = reconcile(&grants, 1, |vault_id| {
+        client.try_get_vault(&vault_id).ok()?.ok()
+    });
+    assert!(report.is_clean(), "{report}");
+
+    client.revoke_partial(&2, &100i128);
+    let report = reconcile(&grants, 1, |vault_id| {
+        client.try_get_vault(&vault_id).ok()?.ok()
+    });
+    assert_eq!(report.lines[0].status, Status::Matched);
+    assert_eq!(
+        report.lines[1].status,
+        Status::Mismatched(vec!["amount", "end"])
+    );
+
+    // Off by one: row 1 is compared with bob's vault and row 2 finds nothing
+    let report = reconcile(&grants, 2, |vault_id| {
+        client.try_get_vault(&vault_id).ok()?.ok()
+    });
+    assert_eq!(
+        report.lines[0].status,
+        Status::Mismatched(vec!["address", "amount", "end"])
+    );
+    assert_eq!(report.lines[1].status, Status::Missing);
+    assert!(report
+        .to_string()
+        .ends_with("2 rows, 0 ok, 1 mismatched, 1 missing; 1500 tokens planned"));
+}
@@ -0,0 +1,107 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// Command-line arguments, consumed option by option. Options may appear in
+/// any order, as `--name value` or `--name=value`; whatever is left once a
+/// command has taken its options is reported as an error.
+pub struct Args {
+    tokens: Vec<String>,
+}
+
+impl Args {
+    pub fn new(tokens: impl IntoIterator<Item = String>) -> Self {
+        Args {
+            tokens: tokens.into_iter().collect(),
+        }
+    }
+
+    /// Take a boolean switch such as `--dry-run`.
+    pub fn flag(&mut self, name: &str) -> bool {
+        match self.tokens.iter().position(|token| token == name) {
+            Some(index) => {
+                self.tokens.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take the first positional token, e.g. the command name.
+    pub fn positional(&mut self) -> Option<String> {
+        match self.tokens.first() {
+            Some(token) if !token.starts_with("--") => Some(self.tokens.remove(0)),
+            _ => None,
+        }
+    }
+
+    /// Take the raw text of `name`, if given.
+    pub fn text(&mut self, name: &str) -> Result<Option<String>, Error> {
+        let prefix = format!("{name}=");
+        for index in 0..self.tokens.len() {
+            if let Some(value) = self.tokens[index].strip_prefix(&prefix) {
+                let value = value.to_string();
+                self.tokens.remove(index);
+                return Ok(Some(value));
+            }
+            if self.tokens[index] == name {
+                if index + 1 == self.tokens.len() {
+                    return Err(Error::Usage(format!("{name} needs a value")));
+                }
+                self.tokens.remove(index);
+                return Ok(Some(self.tokens.remove(index)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn optional<T>(&mut self, name: &str) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.text(name)?.map(|text| parse(name, &text)).transpose()
+    }
+
+    pub fn required<T>(&mut self, name: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.optional(name)?
+            .ok_or_else(|| Error::Usage(format!("missing {name}")))
+    }
+
+    /// Take every occurrence of a repeatable option, in order.
+    pub fn all<T>(&mut self, name: &str) -> Result<Vec<T>, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = self.optional(name)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Fail on anything nobody asked for.
+    pub fn finish(self) -> Result<(), Error> {
+        match self.tokens.first() {
+            Some(token) => Err(Error::Usage(format!("unexpected argument {token}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn parse<T>(arg: &str, text: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse().map_err(|e: T::Err| Error::InvalidArg {
+        arg: arg.to_string(),
+        message: format!("{text}: {e}"),
+    })
+}
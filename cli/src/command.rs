@@ -0,0 +1,294 @@
+use std::fs;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use soroban_sdk::xdr::ScAddress;
+use soroban_sdk::{vec, Address, Env, IntoVal, String as SorobanString, Val, Vec};
+use vesting_contracts::{BatchCreateData, Milestone};
+
+use crate::args::{parse, Args};
+use crate::{Config, Error};
+
+pub const COMMANDS: &str = "\
+    initialize [--admin ADDR] --supply AMOUNT
+    create-vault --owner ADDR --amount AMOUNT --start TIME --end TIME
+                 [--keeper-fee AMOUNT] [--step SECONDS] [--irrevocable] [--non-transferable]
+    batch-create --file ROWS.json [--lazy]
+    revoke --vault ID
+    revoke-partial --vault ID --amount AMOUNT
+    set-milestones --vault ID --milestone ID:PERCENT...
+    unlock-milestone --vault ID --milestone ID
+    top-up --vault ID --amount AMOUNT [--end TIME]
+    set-title --vault ID --title TEXT
+    get-vault --vault ID
+    import --file CAP_TABLE [--chunk-size N] [--lazy] [--first-vault ID]
+    reconcile --file CAP_TABLE --first-vault ID";
+
+/// One row of a `batch-create` file, a JSON array of:
+///
+/// ```text
+/// {"recipient": "GA...", "amount": 1000, "start_time": 1700000000,
+///  "end_time": 1800000000, "keeper_fee": 0, "step_duration": 0}
+/// ```
+///
+/// `keeper_fee` and `step_duration` default to zero.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct VaultRow {
+    pub recipient: String,
+    pub amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    #[serde(default)]
+    pub keeper_fee: i128,
+    #[serde(default)]
+    pub step_duration: u64,
+}
+
+/// `--milestone ID:PERCENT`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MilestoneSpec {
+    pub id: u64,
+    pub percentage: u32,
+}
+
+impl FromStr for MilestoneSpec {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (id, percentage) = text.split_once(':').ok_or("expected ID:PERCENT")?;
+        Ok(MilestoneSpec {
+            id: id.parse().map_err(|e| format!("milestone id: {e}"))?,
+            percentage: percentage.parse().map_err(|e| format!("percentage: {e}"))?,
+        })
+    }
+}
+
+/// A contract call the CLI knows how to build.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Initialize {
+        admin: String,
+        supply: i128,
+    },
+    CreateVault {
+        owner: String,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    },
+    BatchCreate {
+        rows: std::vec::Vec<VaultRow>,
+        lazy: bool,
+    },
+    Revoke {
+        vault_id: u64,
+    },
+    RevokePartial {
+        vault_id: u64,
+        amount: i128,
+    },
+    SetMilestones {
+        vault_id: u64,
+        milestones: std::vec::Vec<MilestoneSpec>,
+    },
+    UnlockMilestone {
+        vault_id: u64,
+        milestone_id: u64,
+    },
+    TopUp {
+        vault_id: u64,
+        amount: i128,
+        end_time: Option<u64>,
+    },
+    SetTitle {
+        vault_id: u64,
+        title: String,
+    },
+    GetVault {
+        vault_id: u64,
+    },
+}
+
+impl Command {
+    /// Parse the command `name`, taking its options from `args`.
+    pub fn parse(name: &str, args: &mut Args, config: &Config) -> Result<Self, Error> {
+        let command = match name {
+            "initialize" => Command::Initialize {
+                admin: args
+                    .text("--admin")?
+                    .unwrap_or_else(|| config.source.clone()),
+                supply: args.required("--supply")?,
+            },
+            "create-vault" => Command::CreateVault {
+                owner: args.required("--owner")?,
+                amount: args.required("--amount")?,
+                start_time: args.required("--start")?,
+                end_time: args.required("--end")?,
+                keeper_fee: args.optional("--keeper-fee")?.unwrap_or(0),
+                is_revocable: !args.flag("--irrevocable"),
+                is_transferable: !args.flag("--non-transferable"),
+                step_duration: args.optional("--step")?.unwrap_or(0),
+            },
+            "batch-create" => {
+                let path: String = args.required("--file")?;
+                let text = fs::read_to_string(&path)?;
+                let rows = serde_json::from_str(&text).map_err(|e| Error::InvalidArg {
+                    arg: "--file".into(),
+                    message: format!("{path}: {e}"),
+                })?;
+                Command::BatchCreate {
+                    rows,
+                    lazy: args.flag("--lazy"),
+                }
+            }
+            "revoke" => Command::Revoke {
+                vault_id: args.required("--vault")?,
+            },
+            "revoke-partial" => Command::RevokePartial {
+                vault_id: args.required("--vault")?,
+                amount: args.required("--amount")?,
+            },
+            "set-milestones" => Command::SetMilestones {
+                vault_id: args.required("--vault")?,
+                milestones: args.all("--milestone")?,
+            },
+            "unlock-milestone" => Command::UnlockMilestone {
+                vault_id: args.required("--vault")?,
+                milestone_id: args.required("--milestone")?,
+            },
+            "top-up" => Command::TopUp {
+                vault_id: args.required("--vault")?,
+                amount: args.required("--amount")?,
+                end_time: args.optional("--end")?,
+            },
+            "set-title" => Command::SetTitle {
+                vault_id: args.required("--vault")?,
+                title: args.required("--title")?,
+            },
+            "get-vault" => Command::GetVault {
+                vault_id: args.required("--vault")?,
+            },
+            _ => return Err(Error::Usage(format!("unknown command {name}"))),
+        };
+        Ok(command)
+    }
+
+    /// Name of the contract function this command invokes
+    pub fn function(&self) -> &'static str {
+        match self {
+            Command::Initialize { .. } => "initialize",
+            Command::CreateVault { .. } => "create_vault_full",
+            Command::BatchCreate { lazy: false, .. } => "batch_create_vaults_full",
+            Command::BatchCreate { lazy: true, .. } => "batch_create_vaults_lazy",
+            Command::Revoke { .. } => "revoke_tokens",
+            Command::RevokePartial { .. } => "revoke_partial",
+            Command::SetMilestones { .. } => "set_milestones",
+            Command::UnlockMilestone { .. } => "unlock_milestone",
+            Command::TopUp { .. } => "top_up_vault",
+            Command::SetTitle { .. } => "set_vault_title",
+            Command::GetVault { .. } => "get_vault",
+        }
+    }
+
+    /// Arguments for [`Command::function`], in contract order.
+    pub fn args(&self, env: &Env) -> Result<Vec<Val>, Error> {
+        let args = match self {
+            Command::Initialize { admin, supply } => vec![
+                env,
+                address(env, "--admin", admin)?.into_val(env),
+                supply.into_val(env),
+            ],
+            Command::CreateVault {
+                owner,
+                amount,
+                start_time,
+                end_time,
+                keeper_fee,
+                is_revocable,
+                is_transferable,
+                step_duration,
+            } => vec![
+                env,
+                address(env, "--owner", owner)?.into_val(env),
+                amount.into_val(env),
+                start_time.into_val(env),
+                end_time.into_val(env),
+                keeper_fee.into_val(env),
+                is_revocable.into_val(env),
+                is_transferable.into_val(env),
+                step_duration.into_val(env),
+            ],
+            Command::BatchCreate { rows, .. } => {
+                let mut batch = BatchCreateData {
+                    recipients: Vec::new(env),
+                    amounts: Vec::new(env),
+                    start_times: Vec::new(env),
+                    end_times: Vec::new(env),
+                    keeper_fees: Vec::new(env),
+                    step_durations: Vec::new(env),
+                };
+                for row in rows {
+                    batch
+                        .recipients
+                        .push_back(address(env, "recipient", &row.recipient)?);
+                    batch.amounts.push_back(row.amount);
+                    batch.start_times.push_back(row.start_time);
+                    batch.end_times.push_back(row.end_time);
+                    batch.keeper_fees.push_back(row.keeper_fee);
+                    batch.step_durations.push_back(row.step_duration);
+                }
+                vec![env, batch.into_val(env)]
+            }
+            Command::Revoke { vault_id } | Command::GetVault { vault_id } => {
+                vec![env, vault_id.into_val(env)]
+            }
+            Command::RevokePartial { vault_id, amount } => {
+                vec![env, vault_id.into_val(env), amount.into_val(env)]
+            }
+            Command::SetMilestones {
+                vault_id,
+                milestones,
+            } => {
+                let mut list = Vec::new(env);
+                for milestone in milestones {
+                    list.push_back(Milestone {
+                        id: milestone.id,
+                        percentage: milestone.percentage,
+                        is_unlocked: false,
+                    });
+                }
+                vec![env, vault_id.into_val(env), list.into_val(env)]
+            }
+            Command::UnlockMilestone {
+                vault_id,
+                milestone_id,
+            } => vec![env, vault_id.into_val(env), milestone_id.into_val(env)],
+            Command::TopUp {
+                vault_id,
+                amount,
+                end_time,
+            } => vec![
+                env,
+                vault_id.into_val(env),
+                amount.into_val(env),
+                end_time.into_val(env),
+            ],
+            Command::SetTitle { vault_id, title } => vec![
+                env,
+                vault_id.into_val(env),
+                SorobanString::from_str(env, title).into_val(env),
+            ],
+        };
+        Ok(args)
+    }
+}
+
+// `Address::from_str` panics on bad input, so check the strkey first
+fn address(env: &Env, arg: &str, text: &str) -> Result<Address, Error> {
+    parse::<ScAddress>(arg, text)?;
+    Ok(Address::from_str(env, text))
+}
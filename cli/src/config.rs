@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::Error;
+
+/// Deployment settings, read from a JSON file:
+///
+/// ```text
+/// {
+///   "source": "GB...",
+///   "fee": 100,
+///   "contracts": { "vesting": "CD..." },
+///   "sandbox": "vesting-sandbox.json"
+/// }
+/// ```
+///
+/// `source` is the admin account that signs, `fee` the inclusion fee in
+/// stroops before simulation adds resource fees, and `sandbox` the ledger
+/// snapshot `--dry-run` works against, relative to the config file.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub source: String,
+    #[serde(default = "default_fee")]
+    pub fee: u32,
+    pub contracts: BTreeMap<String, String>,
+    pub sandbox: Option<PathBuf>,
+}
+
+fn default_fee() -> u32 {
+    100
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let config_error = |message: String| Error::Config {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = fs::read_to_string(path).map_err(|e| config_error(e.to_string()))?;
+        let mut config: Config =
+            serde_json::from_str(&text).map_err(|e| config_error(e.to_string()))?;
+        if let (Some(sandbox), Some(dir)) = (&config.sandbox, path.parent()) {
+            config.sandbox = Some(dir.join(sandbox));
+        }
+        Ok(config)
+    }
+
+    /// Contract ID registered under `name`
+    pub fn contract(&self, name: &str) -> Result<&str, Error> {
+        self.contracts
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| Error::Usage(format!("no contract named {name} in config")))
+    }
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("invalid value for {arg}: {message}")]
+    InvalidArg { arg: String, message: String },
+
+    #[error("config {path}: {message}")]
+    Config { path: String, message: String },
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid xdr: {0}")]
+    Xdr(#[from] stellar_xdr::curr::Error),
+
+    #[error("sandbox: {0}")]
+    Sandbox(String),
+
+    #[error("simulated call failed: {0}")]
+    Simulation(String),
+
+    #[error("{0}")]
+    CapTable(#[from] vesting_captable::Error),
+
+    #[error("vaults do not match the cap table:\n{0}")]
+    Unreconciled(String),
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use soroban_sdk::xdr::ScVal;
+use soroban_sdk::{vec, IntoVal, String as SorobanString};
+use vesting_captable::{batch_data, chunks, load, reconcile as compare, DEFAULT_CHUNK_SIZE};
+use vesting_contracts::VestingContractClient;
+
+use crate::{Args, Error, Sandbox, Submitted, Target};
+
+// `import`: one batch per chunk, then a `set_vault_title` per titled row.
+// In the sandbox the report compares the result with the table; otherwise
+// the transactions are printed one per line, in submission order.
+pub(crate) fn import(mut args: Args, target: &mut Target) -> Result<String, Error> {
+    let path: PathBuf = args.required("--file")?;
+    let chunk_size = args.optional("--chunk-size")?.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let function = if args.flag("--lazy") {
+        "batch_create_vaults_lazy"
+    } else {
+        "batch_create_vaults_full"
+    };
+    let mut first_vault: Option<u64> = args.optional("--first-vault")?;
+    args.finish()?;
+
+    let grants = load(&path)?;
+    if grants.is_empty() {
+        return Err(Error::Usage(format!("{} has no rows", path.display())));
+    }
+    let titled = grants.iter().any(|grant| grant.title.is_some());
+    if titled && first_vault.is_none() && matches!(target, Target::Network { .. }) {
+        return Err(Error::Usage(
+            "rows have titles; pass --first-vault with the id the first vault will get".into(),
+        ));
+    }
+
+    let mut envelopes = Vec::new();
+    for chunk in chunks(&grants, chunk_size) {
+        let batch = batch_data(target.env(), chunk);
+        let args = vec![target.env(), batch.into_val(target.env())];
+        match target.submit(function, args)? {
+            Submitted::Envelope(envelope) => envelopes.push(envelope),
+            Submitted::Outcome(outcome) => {
+                if let (None, ScVal::Vec(Some(ids))) = (first_vault, &outcome.result) {
+                    first_vault = match ids.first() {
+                        Some(ScVal::U64(id)) => Some(*id),
+                        _ => None,
+                    };
+                }
+            }
+        }
+    }
+
+    // Batches hand out consecutive ids, so titles can follow by position
+    if titled {
+        let first_vault = first_vault.expect("checked above or read from the sandbox");
+        for (grant, vault_id) in grants.iter().zip(first_vault..) {
+            if let Some(title) = &grant.title {
+                let env = target.env();
+                let args = vec![
+                    env,
+                    vault_id.into_val(env),
+                    SorobanString::from_str(env, title).into_val(env),
+                ];
+                if let Submitted::Envelope(envelope) = target.submit("set_vault_title", args)? {
+                    envelopes.push(envelope);
+                }
+            }
+        }
+    }
+
+    match target {
+        Target::Sandbox(sandbox) => {
+            let first_vault = first_vault.expect("the first batch returned ids");
+            report(sandbox, &grants, first_vault)
+        }
+        Target::Network { .. } => Ok(envelopes.join("\n")),
+    }
+}
+
+// `reconcile`: compare the table with the vaults in the sandbox, which may be
+// a snapshot of the live network
+pub(crate) fn reconcile(mut args: Args, sandbox: &Sandbox) -> Result<String, Error> {
+    let path: PathBuf = args.required("--file")?;
+    let first_vault = args.required("--first-vault")?;
+    args.finish()?;
+    report(sandbox, &load(&path)?, first_vault)
+}
+
+fn report(
+    sandbox: &Sandbox,
+    grants: &[vesting_captable::Grant],
+    first_vault: u64,
+) -> Result<String, Error> {
+    let client = VestingContractClient::new(sandbox.env(), sandbox.contract());
+    let report = compare(grants, first_vault, |vault_id| {
+        client.try_get_vault(&vault_id).ok().and_then(Result::ok)
+    });
+    if report.is_clean() {
+        Ok(report.to_string())
+    } else {
+        Err(Error::Unreconciled(report.to_string()))
+    }
+}
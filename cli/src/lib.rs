@@ -0,0 +1,187 @@
+//! Admin tooling for `VestingContract`.
+//!
+//! Each [`Command`] maps to one contract call. By default the call is
+//! written out as an unsigned, base64 transaction envelope for the
+//! configured source account, ready for the usual simulate, sign and send
+//! steps. With `--dry-run` it is executed instead against a local
+//! [`Sandbox`], and the result, events and required signers are printed.
+//!
+//! `import` turns a cap table into one batch-create call per chunk (see
+//! `vesting-captable`), and `reconcile` checks the created vaults against it.
+
+mod args;
+mod command;
+mod config;
+mod error;
+mod import;
+mod sandbox;
+mod tx;
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use soroban_sdk::{Env, TryFromVal, Val, Vec};
+use stellar_xdr::curr::{Limits, ScVal, WriteXdr};
+
+pub use args::Args;
+pub use command::{Command, MilestoneSpec, VaultRow, COMMANDS};
+pub use config::Config;
+pub use error::Error;
+pub use sandbox::{Outcome, Sandbox};
+pub use tx::invoke_transaction;
+
+pub const USAGE: &str = "\
+usage: vesting-cli [--config FILE] [--contract NAME] [--sequence N] [--dry-run [--at TIME]]
+                   <command> [options]
+
+    --config FILE    deployment config (default: vesting-cli.json)
+    --contract NAME  contract ID to use from the config (default: vesting)
+    --sequence N     sequence number for the transaction, i.e. the source
+                     account's current sequence plus one
+    --dry-run        run against the config's sandbox instead of printing a
+                     transaction; the sandbox keeps its state between runs
+    --at TIME        sandbox ledger time in unix seconds (default: now)
+
+commands:
+";
+
+/// Run one command line (without the program name) and return what to print.
+pub fn run(tokens: impl IntoIterator<Item = String>) -> Result<String, Error> {
+    let mut args = Args::new(tokens);
+    let config_path = args
+        .text("--config")?
+        .unwrap_or_else(|| "vesting-cli.json".into());
+    let contract_name = args.text("--contract")?.unwrap_or_else(|| "vesting".into());
+    let dry_run = args.flag("--dry-run");
+    let at: Option<u64> = args.optional("--at")?;
+    let sequence: Option<i64> = args.optional("--sequence")?;
+    let name = args
+        .positional()
+        .ok_or_else(|| Error::Usage(format!("{USAGE}{COMMANDS}")))?;
+
+    let config = Config::load(&PathBuf::from(config_path))?;
+    let contract_id = config.contract(&contract_name)?;
+    if name == "reconcile" {
+        let sandbox = open_sandbox(&config, contract_id, at)?;
+        return import::reconcile(args, &sandbox);
+    }
+    let mut target = if dry_run {
+        Target::Sandbox(open_sandbox(&config, contract_id, at)?)
+    } else {
+        Target::Network {
+            config: &config,
+            contract_id,
+            env: Env::new_with_config(sandbox::quiet()),
+            sequence,
+        }
+    };
+
+    let output = if name == "import" {
+        import::import(args, &mut target)?
+    } else {
+        let command = Command::parse(&name, &mut args, &config)?;
+        args.finish()?;
+        let args = command.args(target.env())?;
+        match target.submit(command.function(), args)? {
+            Submitted::Outcome(outcome) => describe(&outcome),
+            Submitted::Envelope(envelope) => envelope,
+        }
+    };
+    if let Target::Sandbox(sandbox) = &target {
+        sandbox.save()?;
+    }
+    Ok(output)
+}
+
+// The sandbox named in the config, with its clock at `at` or else now
+fn open_sandbox(config: &Config, contract_id: &str, at: Option<u64>) -> Result<Sandbox, Error> {
+    let path = config
+        .sandbox
+        .as_ref()
+        .ok_or_else(|| Error::Usage("--dry-run needs a sandbox in the config".into()))?;
+    let timestamp = at.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    });
+    Sandbox::open(path, contract_id, timestamp)
+}
+
+// Where contract calls go: into the sandbox, or out as transactions with
+// consecutive sequence numbers
+enum Target<'a> {
+    Sandbox(Sandbox),
+    Network {
+        config: &'a Config,
+        contract_id: &'a str,
+        env: Env,
+        sequence: Option<i64>,
+    },
+}
+
+enum Submitted {
+    Outcome(Outcome),
+    Envelope(String),
+}
+
+impl Target<'_> {
+    // Env the call arguments are built in
+    fn env(&self) -> &Env {
+        match self {
+            Target::Sandbox(sandbox) => sandbox.env(),
+            Target::Network { env, .. } => env,
+        }
+    }
+
+    fn submit(&mut self, function: &str, args: Vec<Val>) -> Result<Submitted, Error> {
+        match self {
+            Target::Sandbox(sandbox) => Ok(Submitted::Outcome(sandbox.invoke(function, args)?)),
+            Target::Network {
+                config,
+                contract_id,
+                env,
+                sequence,
+            } => {
+                let next = sequence.ok_or_else(|| {
+                    Error::Usage("--sequence is needed to build a transaction".into())
+                })?;
+                *sequence = Some(next + 1);
+                let args = args
+                    .iter()
+                    .map(|arg| ScVal::try_from_val(env, &arg))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| Error::InvalidArg {
+                        arg: function.to_string(),
+                        message: format!("{e:?}"),
+                    })?;
+                let envelope = invoke_transaction(
+                    &config.source,
+                    next,
+                    config.fee,
+                    contract_id,
+                    function,
+                    args,
+                )?;
+                Ok(Submitted::Envelope(envelope.to_xdr_base64(Limits::none())?))
+            }
+        }
+    }
+}
+
+// Dry-run report, one JSON value per line
+fn describe(outcome: &Outcome) -> String {
+    let mut lines = vec![format!("result: {}", to_json(&outcome.result))];
+    for event in &outcome.events {
+        lines.push(format!("event: {}", to_json(event)));
+    }
+    if outcome.signers.is_empty() {
+        lines.push("signers: none".into());
+    } else {
+        lines.push(format!("signers: {}", outcome.signers.join(", ")));
+    }
+    lines.join("\n")
+}
+
+fn to_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string(value).expect("xdr serializes to json")
+}
@@ -0,0 +1,20 @@
+use std::process::ExitCode;
+
+use vesting_cli::Error;
+
+fn main() -> ExitCode {
+    match vesting_cli::run(std::env::args().skip(1)) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(Error::Usage(message)) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
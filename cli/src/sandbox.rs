@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use soroban_ledger_snapshot::LedgerSnapshot;
+use soroban_sdk::testutils::{EnvTestConfig, Events, Ledger};
+use soroban_sdk::xdr::{ContractEvent, LedgerEntryData, ScAddress, ScMap, ScVal};
+use soroban_sdk::{Address, Env, InvokeError, Symbol, TryFromVal, Val, Vec};
+use vesting_contracts::VestingContract;
+
+use crate::Error;
+
+/// Local stand-in for the network. The vesting contract runs natively in a
+/// test `Env` at the configured contract ID, and the ledger is kept in a
+/// snapshot file so successive dry runs build on each other.
+pub struct Sandbox {
+    env: Env,
+    contract: Address,
+    path: PathBuf,
+}
+
+/// What a simulated call returned and touched.
+#[derive(Debug)]
+pub struct Outcome {
+    pub result: ScVal,
+    pub events: std::vec::Vec<ContractEvent>,
+    /// Addresses whose authorization the call required, in order
+    pub signers: std::vec::Vec<std::string::String>,
+}
+
+impl Sandbox {
+    /// Open the snapshot at `path`, or start an empty ledger if there is none
+    /// yet. The ledger clock is set to `timestamp`.
+    pub fn open(path: &Path, contract_id: &str, timestamp: u64) -> Result<Self, Error> {
+        let sandbox_error = |e: soroban_ledger_snapshot::Error| Error::Sandbox(e.to_string());
+        let (env, storage) = if path.exists() {
+            let snapshot = LedgerSnapshot::read_file(path).map_err(sandbox_error)?;
+            let storage = instance_storage(&snapshot, contract_id)?;
+            let mut env = Env::from_ledger_snapshot(snapshot);
+            env.set_config(quiet());
+            (env, storage)
+        } else {
+            (Env::new_with_config(quiet()), None)
+        };
+        env.mock_all_auths();
+        env.ledger().set_timestamp(timestamp);
+
+        // Registering a native contract resets its instance storage, so put
+        // back what the snapshot held
+        let contract = Address::from_str(&env, contract_id);
+        env.register_at(&contract, VestingContract, ());
+        if let Some(storage) = storage {
+            env.as_contract(&contract, || -> Result<(), Error> {
+                for entry in storage.iter() {
+                    let key = Val::try_from_val(&env, &entry.key).map_err(host_error)?;
+                    let val = Val::try_from_val(&env, &entry.val).map_err(host_error)?;
+                    env.storage().instance().set(&key, &val);
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(Sandbox {
+            env,
+            contract,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    pub fn contract(&self) -> &Address {
+        &self.contract
+    }
+
+    /// Call `function` with every authorization mocked.
+    pub fn invoke(&self, function: &str, args: Vec<Val>) -> Result<Outcome, Error> {
+        let result = self
+            .env
+            .try_invoke_contract::<Val, InvokeError>(
+                &self.contract,
+                &Symbol::new(&self.env, function),
+                args,
+            )
+            .map_err(|e| Error::Simulation(format!("{e:?}")))?
+            .map_err(|e| Error::Simulation(format!("{e:?}")))?;
+
+        let events = self
+            .env
+            .events()
+            .all()
+            .filter_by_contract(&self.contract)
+            .events()
+            .to_vec();
+        let signers = self
+            .env
+            .auths()
+            .into_iter()
+            .map(|(address, _)| address.to_string().to_string())
+            .collect();
+        Ok(Outcome {
+            result: ScVal::try_from_val(&self.env, &result).map_err(host_error)?,
+            events,
+            signers,
+        })
+    }
+
+    /// Write the ledger back to the snapshot file.
+    pub fn save(&self) -> Result<(), Error> {
+        self.env
+            .to_ledger_snapshot()
+            .write_file(&self.path)
+            .map_err(|e| Error::Sandbox(e.to_string()))
+    }
+}
+
+// Test `Env`s write a snapshot of themselves when dropped inside a test,
+// which is never wanted here
+pub(crate) fn quiet() -> EnvTestConfig {
+    EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    }
+}
+
+// The contract's instance storage as recorded in `snapshot`
+fn instance_storage(snapshot: &LedgerSnapshot, contract_id: &str) -> Result<Option<ScMap>, Error> {
+    let contract: ScAddress = contract_id.parse()?;
+    for (_, (entry, _)) in &snapshot.ledger_entries {
+        if let LedgerEntryData::ContractData(data) = &entry.data {
+            if data.contract == contract && data.key == ScVal::LedgerKeyContractInstance {
+                if let ScVal::ContractInstance(instance) = &data.val {
+                    return Ok(instance.storage.clone());
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn host_error(e: impl std::fmt::Debug) -> Error {
+    Error::Sandbox(format!("{e:?}"))
+}
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use stellar_xdr::curr::{
+    AccountId, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Memo, MuxedAccount,
+    Operation, OperationBody, Preconditions, PublicKey, ScAddress, ScSymbol, ScVal, SequenceNumber,
+    Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope,
+};
+
+use crate::Error;
+
+/// Build an unsigned transaction from `source` invoking `function` on
+/// `contract`.
+///
+/// The envelope carries no footprint, resource fee or auth entries yet; run
+/// it through simulation (`stellar tx simulate`) before signing and
+/// submitting it.
+pub fn invoke_transaction(
+    source: &str,
+    sequence: i64,
+    fee: u32,
+    contract: &str,
+    function: &str,
+    args: Vec<ScVal>,
+) -> Result<TransactionEnvelope, Error> {
+    let source_account = match ScAddress::from_str(source) {
+        Ok(ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(key)))) => {
+            MuxedAccount::Ed25519(key)
+        }
+        _ => {
+            return Err(Error::InvalidArg {
+                arg: "source".into(),
+                message: format!("{source} is not an account address"),
+            })
+        }
+    };
+    let contract_address = match ScAddress::from_str(contract) {
+        Ok(address @ ScAddress::Contract(_)) => address,
+        _ => {
+            return Err(Error::InvalidArg {
+                arg: "contract".into(),
+                message: format!("{contract} is not a contract address"),
+            })
+        }
+    };
+
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address,
+                function_name: ScSymbol(function.try_into()?),
+                args: args.try_into()?,
+            }),
+            auth: Default::default(),
+        }),
+    };
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: Transaction {
+            source_account,
+            fee,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![operation].try_into()?,
+            ext: TransactionExt::V0,
+        },
+        signatures: Default::default(),
+    }))
+}
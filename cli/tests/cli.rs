@@ -0,0 +1,259 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use soroban_sdk::xdr::{
+    AccountId, ContractId, Hash, HostFunction, Limits, OperationBody, PublicKey, ReadXdr,
+    ScAddress, ScVal, TransactionEnvelope, Uint256,
+};
+use vesting_cli::{run, Error, Sandbox};
+use vesting_contracts::VestingContractClient;
+
+const SOURCE: [u8; 32] = [1; 32];
+const OWNER: [u8; 32] = [2; 32];
+const CONTRACT: [u8; 32] = [3; 32];
+
+fn account(key: [u8; 32]) -> String {
+    ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key)))).to_string()
+}
+
+fn contract() -> String {
+    ScAddress::Contract(ContractId(Hash(CONTRACT))).to_string()
+}
+
+// Fresh directory holding a config whose sandbox lives next to it
+fn setup(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vesting-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let config = serde_json::json!({
+        "source": account(SOURCE),
+        "fee": 250,
+        "contracts": { "vesting": contract() },
+        "sandbox": "sandbox.json",
+    });
+    fs::write(dir.join("vesting-cli.json"), config.to_string()).unwrap();
+    dir
+}
+
+fn cli(dir: &Path, args: &[&str]) -> Result<String, Error> {
+    let config = dir.join("vesting-cli.json").display().to_string();
+    run(["--config", config.as_str()]
+        .iter()
+        .chain(args)
+        .map(|arg| arg.to_string()))
+}
+
+#[test]
+fn test_create_vault_transaction() {
+    let dir = setup("tx");
+    let owner = account(OWNER);
+    let output = cli(
+        &dir,
+        &[
+            "--sequence",
+            "42",
+            "create-vault",
+            "--owner",
+            &owner,
+            "--amount",
+            "1000",
+            "--start",
+            "100",
+            "--end=1100",
+            "--step",
+            "100",
+            "--irrevocable",
+        ],
+    )
+    .unwrap();
+
+    let TransactionEnvelope::Tx(envelope) =
+        TransactionEnvelope::from_xdr_base64(&output, Limits::none()).unwrap()
+    else {
+        panic!("expected a v1 envelope");
+    };
+    assert!(envelope.signatures.is_empty());
+    assert_eq!(envelope.tx.seq_num.0, 42);
+    assert_eq!(envelope.tx.fee, 250);
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("expected invoke_host_function");
+    };
+    let HostFunction::InvokeContract(call) = &op.host_function else {
+        panic!("expected a contract call");
+    };
+    assert_eq!(call.contract_address.to_string(), contract());
+    assert_eq!(
+        call.function_name.0.to_utf8_string_lossy(),
+        "create_vault_full"
+    );
+    assert_eq!(
+        call.args.to_vec(),
+        [
+            ScVal::Address(owner.parse().unwrap()),
+            ScVal::from(1000i128),
+            ScVal::U64(100),
+            ScVal::U64(1100),
+            ScVal::from(0i128),
+            ScVal::Bool(false),
+            ScVal::Bool(true),
+            ScVal::U64(100),
+        ]
+    );
+}
+
+#[test]
+fn test_dry_run_builds_on_sandbox_state() {
+    let dir = setup("dry-run");
+    let owner = account(OWNER);
+    let contract = contract();
+    let dry_run = |args: &[&str]| {
+        let mut full = vec!["--dry-run", "--at", "1000"];
+        full.extend_from_slice(args);
+        cli(&dir, &full)
+    };
+
+    // The contract only accepts admin calls when it is its own admin
+    dry_run(&["initialize", "--admin", &contract, "--supply", "1000000"]).unwrap();
+    let create = [
+        "create-vault",
+        "--owner",
+        &owner,
+        "--amount",
+        "1000",
+        "--start",
+        "1000",
+        "--end",
+        "2000",
+    ];
+    let created = dry_run(&create).unwrap();
+    assert!(created.starts_with("result: {\"u64\":\"1\"}"), "{created}");
+    let revoked = dry_run(&["revoke-partial", "--vault", "1", "--amount", "200"]).unwrap();
+    assert!(revoked.contains("\"tokens_revoked\""), "{revoked}");
+
+    // A failing call leaves the sandbox as it was
+    assert!(matches!(
+        dry_run(&["revoke-partial", "--vault", "1", "--amount", "900"]),
+        Err(Error::Simulation(_))
+    ));
+
+    dry_run(&create).unwrap();
+    dry_run(&[
+        "set-milestones",
+        "--vault",
+        "2",
+        "--milestone",
+        "1:40",
+        "--milestone",
+        "2:60",
+    ])
+    .unwrap();
+    dry_run(&["unlock-milestone", "--vault", "2", "--milestone", "1"]).unwrap();
+
+    let sandbox = Sandbox::open(&dir.join("sandbox.json"), &contract, 1000).unwrap();
+    let client = VestingContractClient::new(sandbox.env(), sandbox.contract());
+    let vault = client.get_vault(&1);
+    assert_eq!(vault.owner.to_string().to_string(), owner);
+    assert_eq!(vault.total_amount, 800);
+    assert_eq!(vault.end_time, 1800);
+    let milestones = client.get_milestones(&2);
+    assert!(milestones.get(0).unwrap().is_unlocked);
+    assert!(!milestones.get(1).unwrap().is_unlocked);
+}
+
+#[test]
+fn test_usage_errors() {
+    let dir = setup("usage");
+    assert!(matches!(
+        cli(&dir, &["revoke", "--vault", "1"]),
+        Err(Error::Usage(message)) if message.contains("--sequence")
+    ));
+    assert!(matches!(
+        cli(&dir, &["--sequence", "1", "burn", "--vault", "1"]),
+        Err(Error::Usage(message)) if message.contains("unknown command burn")
+    ));
+    assert!(matches!(
+        cli(&dir, &["--sequence", "1", "revoke", "--vault", "1", "--amount", "5"]),
+        Err(Error::Usage(message)) if message.contains("unexpected argument --amount")
+    ));
+    assert!(matches!(
+        cli(&dir, &["--sequence", "1", "create-vault", "--owner", "nobody", "--amount", "1",
+            "--start", "0", "--end", "1"]),
+        Err(Error::InvalidArg { arg, .. }) if arg == "--owner"
+    ));
+}
+
+#[test]
+fn test_import_cap_table() {
+    let dir = setup("import");
+    let owner = account(OWNER);
+    let contract = contract();
+    let table = dir.join("cap.csv");
+    fs::write(
+        &table,
+        format!(
+            "address,amount,start,end,step,title\n\
+             {owner},1000,1000,2000,100,Founder\n\
+             {owner},500,1000,2000,,\n\
+             {owner},250,1000,3000,,Advisor\n"
+        ),
+    )
+    .unwrap();
+    let file = table.display().to_string();
+
+    // Titled rows need to know their vault ids before anything is submitted
+    assert!(matches!(
+        cli(&dir, &["--sequence", "7", "import", "--file", &file]),
+        Err(Error::Usage(message)) if message.contains("--first-vault")
+    ));
+    let output = cli(
+        &dir,
+        &[
+            "--sequence",
+            "7",
+            "import",
+            "--file",
+            &file,
+            "--chunk-size",
+            "2",
+            "--first-vault",
+            "1",
+        ],
+    )
+    .unwrap();
+    let sequences: Vec<i64> = output
+        .lines()
+        .map(|line| {
+            let TransactionEnvelope::Tx(envelope) =
+                TransactionEnvelope::from_xdr_base64(line, Limits::none()).unwrap()
+            else {
+                panic!("expected a v1 envelope");
+            };
+            envelope.tx.seq_num.0
+        })
+        .collect();
+    // Two batches, then two titles
+    assert_eq!(sequences, [7, 8, 9, 10]);
+
+    let dry_run = |args: &[&str]| {
+        let mut full = vec!["--dry-run", "--at", "1000"];
+        full.extend_from_slice(args);
+        cli(&dir, &full)
+    };
+    dry_run(&["initialize", "--admin", &contract, "--supply", "1000000"]).unwrap();
+    let report = dry_run(&["import", "--file", &file, "--chunk-size", "2"]).unwrap();
+    assert!(report.contains("3 rows, 3 ok"), "{report}");
+
+    let title = {
+        let sandbox = Sandbox::open(&dir.join("sandbox.json"), &contract, 1000).unwrap();
+        let client = VestingContractClient::new(sandbox.env(), sandbox.contract());
+        client.get_vault(&3).title.to_string()
+    };
+    assert_eq!(title, "Advisor");
+
+    dry_run(&["reconcile", "--file", &file, "--first-vault", "1"]).unwrap();
+    dry_run(&["revoke-partial", "--vault", "2", "--amount", "100"]).unwrap();
+    assert!(matches!(
+        dry_run(&["reconcile", "--file", &file, "--first-vault", "1"]),
+        Err(Error::Unreconciled(report)) if report.contains("1 mismatched")
+    ));
+}
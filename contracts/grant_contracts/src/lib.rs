@@ -1,5 +1,5 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Symbol, Vec, Map, U256};
+use soroban_sdk::{contract, contractevent, contractimpl, symbol_short, Address, Env, Symbol, U256};
 
 #[contract]
 pub struct GrantContract;
@@ -10,6 +10,27 @@ const END_TIME: Symbol = symbol_short!("END");
 const RECIPIENT: Symbol = symbol_short!("RECIPIENT");
 const CLAIMED: Symbol = symbol_short!("CLAIMED");
 
+// Events follow the vesting contract's schema: (name, "v1", indexed fields...)
+// topics with the remaining fields as a map
+#[contractevent(topics = ["grant_initialized", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrantInitialized {
+    #[topic]
+    pub recipient: Address,
+    pub total_amount: U256,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[contractevent(topics = ["grant_claimed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrantClaimed {
+    #[topic]
+    pub recipient: Address,
+    pub amount: U256,
+    pub total_claimed: U256,
+}
+
 #[contractimpl]
 impl GrantContract {
     pub fn initialize_grant(
@@ -20,69 +41,89 @@ impl GrantContract {
     ) -> u64 {
         let start_time = env.ledger().timestamp();
         let end_time = start_time + duration_seconds;
-        
+
         env.storage().instance().set(&TOTAL_AMOUNT, &total_amount);
         env.storage().instance().set(&START_TIME, &start_time);
         env.storage().instance().set(&END_TIME, &end_time);
         env.storage().instance().set(&RECIPIENT, &recipient);
-        env.storage().instance().set(&CLAIMED, &U256::from_u64(0));
-        
+        env.storage().instance().set(&CLAIMED, &U256::from_u32(&env, 0));
+
+        GrantInitialized {
+            recipient,
+            total_amount,
+            start_time,
+            end_time,
+        }
+        .publish(&env);
+
         end_time
     }
-    
+
     pub fn claimable_balance(env: Env) -> U256 {
+        let zero = U256::from_u32(&env, 0);
         let current_time = env.ledger().timestamp();
-        let start_time = env.storage().instance().get(&START_TIME).unwrap_or(0);
-        let end_time = env.storage().instance().get(&END_TIME).unwrap_or(0);
-        let total_amount = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(U256::from_u64(0));
-        let claimed = env.storage().instance().get(&CLAIMED).unwrap_or(U256::from_u64(0));
-        
+        let start_time: u64 = env.storage().instance().get(&START_TIME).unwrap_or(0);
+        let end_time: u64 = env.storage().instance().get(&END_TIME).unwrap_or(0);
+        let total_amount: U256 = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(zero.clone());
+        let claimed: U256 = env.storage().instance().get(&CLAIMED).unwrap_or(zero.clone());
+
         if current_time <= start_time {
-            return U256::from_u64(0);
+            return zero;
         }
-        
+
         let elapsed = if current_time >= end_time {
             end_time - start_time
         } else {
             current_time - start_time
         };
-        
+
         let total_duration = end_time - start_time;
         let vested = if total_duration > 0 {
-            total_amount * U256::from_u64(elapsed) / U256::from_u64(total_duration)
+            total_amount
+                .mul(&U256::from_u128(&env, elapsed as u128))
+                .div(&U256::from_u128(&env, total_duration as u128))
         } else {
-            U256::from_u64(0)
+            zero.clone()
         };
-        
+
         if vested > claimed {
-            vested - claimed
+            vested.sub(&claimed)
         } else {
-            U256::from_u64(0)
+            zero
         }
     }
-    
+
     pub fn claim(env: Env, recipient: Address) -> U256 {
         recipient.require_auth();
-        
-        let stored_recipient = env.storage().instance().get(&RECIPIENT).unwrap();
+
+        let stored_recipient: Address = env.storage().instance().get(&RECIPIENT).unwrap();
         assert_eq!(recipient, stored_recipient, "Unauthorized recipient");
-        
+
+        let zero = U256::from_u32(&env, 0);
         let claimable = Self::claimable_balance(env.clone());
-        assert!(claimable > U256::from_u64(0), "No tokens to claim");
-        
-        let claimed = env.storage().instance().get(&CLAIMED).unwrap_or(U256::from_u64(0));
-        let new_claimed = claimed + claimable;
+        assert!(claimable > zero, "No tokens to claim");
+
+        let claimed: U256 = env.storage().instance().get(&CLAIMED).unwrap_or(zero);
+        let new_claimed = claimed.add(&claimable);
         env.storage().instance().set(&CLAIMED, &new_claimed);
-        
+
+        GrantClaimed {
+            recipient,
+            amount: claimable.clone(),
+            total_claimed: new_claimed,
+        }
+        .publish(&env);
+
         claimable
     }
-    
+
     pub fn get_grant_info(env: Env) -> (U256, u64, u64, U256) {
-        let total_amount = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(U256::from_u64(0));
+        let zero = U256::from_u32(&env, 0);
+        let total_amount = env.storage().instance().get(&TOTAL_AMOUNT).unwrap_or(zero.clone());
         let start_time = env.storage().instance().get(&START_TIME).unwrap_or(0);
         let end_time = env.storage().instance().get(&END_TIME).unwrap_or(0);
-        let claimed = env.storage().instance().get(&CLAIMED).unwrap_or(U256::from_u64(0));
-        
+        let claimed = env.storage().instance().get(&CLAIMED).unwrap_or(zero);
+
         (total_amount, start_time, end_time, claimed)
     }
 }
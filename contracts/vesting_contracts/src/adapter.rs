@@ -0,0 +1,18 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Interface that every staking / yield adapter approved by the vesting
+/// contract must implement. Positions are tracked per vault id.
+#[contractclient(name = "StakingAdapterClient")]
+pub trait StakingAdapter {
+    /// Stake `amount` tokens of `vault_id` with `validator`.
+    fn stake(env: Env, vault_id: u64, amount: i128, validator: Address);
+
+    /// Unstake `amount` tokens of `vault_id` from `validator`.
+    fn unstake(env: Env, vault_id: u64, amount: i128, validator: Address);
+
+    /// Tokens currently staked for `vault_id`.
+    fn balance_of(env: Env, vault_id: u64) -> i128;
+
+    /// Rewards accrued for `vault_id` that have not been paid out yet.
+    fn pending_rewards(env: Env, vault_id: u64) -> i128;
+}
@@ -0,0 +1,27 @@
+//! Typed errors for `VestingContract`.
+//!
+//! Most entry points still panic with a message; the errors below cover vault
+//! creation, where callers (and batch tooling) need to tell bad parameters
+//! apart. Single creation panics with them and `validate_batch` reports them
+//! per row. They surface as `Error(Contract, #code)`, so codes never change
+//! once assigned.
+
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum VestingError {
+    NonPositiveAmount = 1,
+    EndNotAfterStart = 2,
+    NegativeKeeperFee = 3,
+    KeeperFeeExceedsAmount = 4,
+    StepExceedsDuration = 5,
+    // amount * duration must fit in i128 for the vesting math
+    AmountTooLarge = 6,
+    // Batch rows only
+    TokenNotWhitelisted = 7,
+    DuplicateRow = 8,               // Same recipient and schedule as an earlier row
+    InsufficientBalance = 9,        // The batch runs out of admin balance at this row
+    StepDoesNotDivideDuration = 10, // Batches only take whole steps
+}
@@ -0,0 +1,551 @@
+//! Event schema for `VestingContract`.
+//!
+//! Every state change publishes one of the structs below. The fixed topics are
+//! the event name and its schema version, e.g. `("tokens_claimed", "v1",
+//! vault_id)`; fields marked `#[topic]` follow as indexed topics and the rest
+//! is published as a map keyed by field name. A breaking change to an event
+//! adds a `v2` struct next to the old one instead of editing it, so indexers
+//! can keep decoding history.
+
+use soroban_sdk::{contractevent, contracttype, Address, String, Vec};
+
+use crate::{
+    AccelerationRule, DelegateScope, PayoutSplit, RevocationPolicy, ScheduleAmendment,
+    TerminationReason, TransferPolicy,
+};
+
+// How a vault came to change owner
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    Admin,       // transfer_beneficiary
+    Owner,       // transfer_vault / NFT transfer, accepted by the recipient
+    KeyRotation, // rotate_beneficiary_key
+    Recovery,    // complete_recovery
+}
+
+// --- Contract administration ---
+
+#[contractevent(topics = ["contract_initialized", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractInitialized {
+    #[topic]
+    pub admin: Address,
+    pub initial_supply: i128,
+}
+
+#[contractevent(topics = ["admin_proposed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdminProposed {
+    #[topic]
+    pub proposed_admin: Address,
+}
+
+#[contractevent(topics = ["admin_transferred", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdminTransferred {
+    pub previous_admin: Address,
+    #[topic]
+    pub new_admin: Address,
+}
+
+#[contractevent(topics = ["token_whitelisted", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenWhitelisted {
+    #[topic]
+    pub token: Address,
+}
+
+// --- Vault lifecycle ---
+
+#[contractevent(topics = ["vault_created", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultCreated {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub beneficiary: Address,
+    pub total_amount: i128,
+    pub cliff_duration: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_duration: u64,
+    pub keeper_fee: i128,
+    pub is_irrevocable: bool,
+    pub is_transferable: bool,
+    pub is_initialized: bool, // False for lazily created vaults until VaultInitialized
+    pub title: String,
+}
+
+#[contractevent(topics = ["vault_initialized", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultInitialized {
+    #[topic]
+    pub vault_id: u64,
+    pub owner: Address,
+}
+
+#[contractevent(topics = ["vault_title_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultTitleSet {
+    #[topic]
+    pub vault_id: u64,
+    pub title: String,
+}
+
+#[contractevent(topics = ["irrevocable_marked", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IrrevocableMarked {
+    #[topic]
+    pub vault_id: u64,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["milestones_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestonesSet {
+    #[topic]
+    pub vault_id: u64,
+    pub count: u32,
+    pub total_percentage: u32,
+}
+
+#[contractevent(topics = ["milestone_unlocked", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneUnlocked {
+    #[topic]
+    pub vault_id: u64,
+    pub milestone_id: u64,
+    pub timestamp: u64,
+}
+
+// --- Claims and payouts ---
+
+#[contractevent(topics = ["tokens_claimed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokensClaimed {
+    #[topic]
+    pub vault_id: u64,
+    pub amount: i128,
+    pub released_amount: i128, // Total claimed from the vault so far
+}
+
+#[contractevent(topics = ["tokens_paid", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokensPaid {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["claim_all_paid", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimAllPaid {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["claim_all_summary", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimAllSummary {
+    #[topic]
+    pub owner: Address,
+    pub vaults_claimed: u32,
+    pub total_claimed: i128,
+}
+
+#[contractevent(topics = ["auto_claimed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoClaimed {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub keeper: Address,
+    pub beneficiary_amount: i128,
+    pub keeper_fee: i128,
+}
+
+#[contractevent(topics = ["payout_splits_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutSplitsSet {
+    #[topic]
+    pub vault_id: u64,
+    pub splits: Vec<PayoutSplit>,
+}
+
+// --- Ownership and delegation ---
+
+#[contractevent(topics = ["vault_transferred", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultTransferred {
+    #[topic]
+    pub vault_id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub kind: TransferKind,
+}
+
+#[contractevent(topics = ["delegate_updated", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegateUpdated {
+    #[topic]
+    pub vault_id: u64,
+    pub old_delegate: Option<Address>,
+    pub new_delegate: Option<Address>,
+}
+
+#[contractevent(topics = ["delegate_added", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegateAdded {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub delegate: Address,
+    pub scopes: Vec<DelegateScope>,
+    pub expires_at: u64,
+    pub claim_cap: i128,
+    pub cap_period: u64,
+}
+
+#[contractevent(topics = ["delegate_removed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegateRemoved {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub delegate: Address,
+}
+
+#[contractevent(topics = ["vote_delegate_changed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteDelegateChanged {
+    #[topic]
+    pub owner: Address,
+    pub old_delegatee: Address,
+    pub new_delegatee: Address,
+}
+
+#[contractevent(topics = ["vault_split", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultSplit {
+    #[topic]
+    pub vault_id: u64,
+    pub child_vault_id: u64,
+    pub new_owner: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["vaults_merged", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultsMerged {
+    #[topic]
+    pub vault_id: u64,
+    pub merged_vault_id: u64,
+    pub merged_amount: i128,
+}
+
+// --- Transfer restrictions ---
+
+#[contractevent(topics = ["transfer_policy_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferPolicySet {
+    pub policy: TransferPolicy,
+}
+
+#[contractevent(topics = ["transfer_recipient_added", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferRecipientAdded {
+    #[topic]
+    pub recipient: Address,
+}
+
+#[contractevent(topics = ["transfer_recipient_removed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferRecipientRemoved {
+    #[topic]
+    pub recipient: Address,
+}
+
+#[contractevent(topics = ["transfer_requested", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferRequested {
+    #[topic]
+    pub vault_id: u64,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[contractevent(topics = ["transfer_approved", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferApproved {
+    #[topic]
+    pub vault_id: u64,
+    pub to: Address,
+}
+
+#[contractevent(topics = ["transfer_cancelled", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferCancelled {
+    #[topic]
+    pub vault_id: u64,
+    pub to: Address,
+}
+
+// --- Guardian recovery ---
+
+#[contractevent(topics = ["guardians_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardiansSet {
+    #[topic]
+    pub vault_id: u64,
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[contractevent(topics = ["recovery_delay_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryDelaySet {
+    pub delay: u64,
+}
+
+#[contractevent(topics = ["recovery_initiated", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryInitiated {
+    #[topic]
+    pub vault_id: u64,
+    pub guardian: Address,
+    pub new_owner: Address,
+}
+
+#[contractevent(topics = ["recovery_approved", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryApproved {
+    #[topic]
+    pub vault_id: u64,
+    pub guardian: Address,
+    pub approvals: u32,
+}
+
+#[contractevent(topics = ["recovery_cancelled", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryCancelled {
+    #[topic]
+    pub vault_id: u64,
+    pub new_owner: Address,
+}
+
+// --- Revocation, termination and acceleration ---
+
+#[contractevent(topics = ["tokens_revoked", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokensRevoked {
+    #[topic]
+    pub vault_id: u64,
+    #[topic]
+    pub beneficiary: Address,
+    pub vested_amount: i128,
+    pub unvested_amount: i128,
+    pub timestamp: u64,
+}
+
+// The schedule left after a revocation or retirement cut it short
+#[contractevent(topics = ["schedule_shortened", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleShortened {
+    #[topic]
+    pub vault_id: u64,
+    pub total_amount: i128,
+    pub end_time: u64,
+    pub step_duration: u64,
+    pub suspended_at: Option<u64>,
+}
+
+#[contractevent(topics = ["vault_clawed_back", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultClawedBack {
+    #[topic]
+    pub vault_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["revocation_policy_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevocationPolicySet {
+    #[topic]
+    pub vault_id: u64,
+    pub policy: RevocationPolicy,
+}
+
+#[contractevent(topics = ["vault_terminated", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultTerminated {
+    #[topic]
+    pub vault_id: u64,
+    pub reason: TerminationReason,
+    pub effective_time: u64,
+    pub forfeited: i128,
+}
+
+#[contractevent(topics = ["vesting_accelerated", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestingAccelerated {
+    #[topic]
+    pub vault_id: u64,
+    pub bps: u32,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["acceleration_rule_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccelerationRuleSet {
+    #[topic]
+    pub vault_id: u64,
+    pub rule: AccelerationRule,
+}
+
+#[contractevent(topics = ["change_of_control", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeOfControl {
+    pub timestamp: u64,
+}
+
+// --- Schedule changes ---
+
+#[contractevent(topics = ["vault_topped_up", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultToppedUp {
+    #[topic]
+    pub vault_id: u64,
+    pub amount: i128,
+    pub total_amount: i128,
+    pub end_time: u64,
+}
+
+#[contractevent(topics = ["amendment_proposed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmendmentProposed {
+    #[topic]
+    pub vault_id: u64,
+    pub amendment: ScheduleAmendment,
+}
+
+#[contractevent(topics = ["amendment_cancelled", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmendmentCancelled {
+    #[topic]
+    pub vault_id: u64,
+}
+
+#[contractevent(topics = ["schedule_amended", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleAmended {
+    #[topic]
+    pub vault_id: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_duration: u64,
+}
+
+#[contractevent(topics = ["vesting_suspended", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestingSuspended {
+    #[topic]
+    pub vault_id: u64,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["vesting_resumed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestingResumed {
+    #[topic]
+    pub vault_id: u64,
+    pub timestamp: u64,
+    pub extension: u64,
+}
+
+// --- Staking ---
+
+#[contractevent(topics = ["staking_adapter_added", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakingAdapterAdded {
+    #[topic]
+    pub adapter: Address,
+    pub is_default: bool,
+}
+
+#[contractevent(topics = ["staking_adapter_removed", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakingAdapterRemoved {
+    #[topic]
+    pub adapter: Address,
+}
+
+#[contractevent(topics = ["vault_adapter_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultAdapterSet {
+    #[topic]
+    pub vault_id: u64,
+    pub adapter: Address,
+}
+
+#[contractevent(topics = ["unbonding_period_set", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnbondingPeriodSet {
+    pub period: u64,
+}
+
+#[contractevent(topics = ["tokens_staked", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokensStaked {
+    #[topic]
+    pub vault_id: u64,
+    pub validator: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["tokens_unstaked", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokensUnstaked {
+    #[topic]
+    pub vault_id: u64,
+    pub amount: i128,
+    pub release_time: u64, // When the tokens become claimable again
+}
+
+#[contractevent(topics = ["redelegated", "v1"])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Redelegated {
+    #[topic]
+    pub vault_id: u64,
+    pub from_validator: Address,
+    pub to_validator: Address,
+    pub amount: i128,
+}
+
+// --- NFT view ---
+// These follow the usual NFT event shape rather than the versioned schema
+// above, so wallets and marketplaces can index vault positions unchanged.
+
+#[contractevent(topics = ["transfer"], data_format = "single-value")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NftTransfer {
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub to: Address,
+    pub token_id: u64,
+}
+
+#[contractevent(topics = ["approve"], data_format = "vec")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NftApprove {
+    #[topic]
+    pub owner: Address,
+    pub token_id: u64,
+    pub approved: Option<Address>,
+}
@@ -1,5 +1,21 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Map, Symbol, Vec, String};
+// Entry points mirror the vault fields, and the generated client repeats them
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{contract, contractimpl, contracttype, panic_with_error, Address, Env, Error, Map, Vec, String};
+
+// DataKey for core contract state
+#[contracttype]
+pub enum DataKey {
+    InitialSupply,
+    AdminBalance,
+    AdminAddress,
+    ProposedAdmin,
+    VaultCount,
+    VaultData(u64),       // Vault
+    UserVaults(Address),  // Vec<u64> of an owner's initialized vaults
+    VaultMilestones(u64), // Vec<Milestone> of a milestone-based vault
+    KeeperFees,           // Map<Address, i128> of fees earned through auto_claim
+}
 
 // DataKey for whitelisted tokens
 #[contracttype]
@@ -7,12 +23,28 @@ pub enum WhitelistDataKey {
     WhitelistedTokens,
 }
 
+mod adapter;
+mod errors;
 mod factory;
+mod events;
+mod nft;
+pub use adapter::{StakingAdapter, StakingAdapterClient};
+pub use errors::VestingError;
+pub use events::*;
+pub use nft::NftDataKey;
 pub use factory::{VestingFactory, VestingFactoryClient};
 
 #[contract]
 pub struct VestingContract;
 
+// Maximum number of scoped delegates per vault
+const MAX_DELEGATES: u32 = 10;
+// Maximum number of payout splits per vault
+const MAX_PAYOUT_SPLITS: u32 = 5;
+const BPS_DENOMINATOR: u32 = 10_000;
+const MAX_GUARDIANS: u32 = 10;
+const DEFAULT_RECOVERY_DELAY: u64 = 7 * 24 * 60 * 60; // 7 days
+
 
 
 // Vault structure with lazy initialization
@@ -33,6 +65,136 @@ pub struct Vault {
     pub is_transferable: bool, // Can the beneficiary transfer this vault?
     pub step_duration: u64, // Duration of each vesting step in seconds (0 = linear)
     pub staked_amount: i128, // Amount currently staked in external contract
+    pub revocation_policy: RevocationPolicy, // Outcome applied by terminate()
+    pub suspended_at: Option<u64>, // Start of the current suspension, if vesting is paused
+    pub suspended_duration: u64, // Total completed suspension time after start_time (already added to end_time)
+}
+
+// What happens to a vault when its beneficiary leaves
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationPolicy {
+    ForfeitUnvested,  // Every termination forfeits what is unvested at the effective time
+    LeaverTerms(u64), // Bad leavers forfeit, good leavers keep vesting for N more seconds, retirees vest fully
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminationReason {
+    BadLeaver,
+    GoodLeaver,
+    Retirement,
+}
+
+// DataKey for lazily created vaults
+#[contracttype]
+pub enum LazyDataKey {
+    PendingVaults(Address), // Vec<u64> of an owner's vaults still awaiting initialization
+}
+
+// DataKey for vesting suspensions
+#[contracttype]
+pub enum SuspensionDataKey {
+    History(u64), // Vec<SuspensionPeriod> for a vault, oldest first
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuspensionPeriod {
+    pub suspended_at: u64,
+    pub resumed_at: Option<u64>, // None while the suspension is ongoing
+}
+
+// DataKey for top-ups and schedule amendments
+#[contracttype]
+pub enum AmendmentDataKey {
+    Pending(u64),         // ScheduleAmendment awaiting the beneficiary's consent
+    ScheduleHistory(u64), // Vec<ScheduleVersion> of a vault's replaced schedules, oldest first
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleAmendment {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_duration: u64,
+    pub proposed_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleVersion {
+    pub total_amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_duration: u64,
+    pub replaced_at: u64,
+}
+
+// DataKey for vault transfer restrictions
+#[contracttype]
+pub enum TransferDataKey {
+    Policy,       // TransferPolicy applied to every vault transfer
+    AllowList,    // Vec<Address> of permitted recipients (when the policy requires it)
+    Pending(u64), // PendingTransfer awaiting the recipient's acceptance
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferPolicy {
+    pub requires_admin_approval: bool, // Admin must approve each pending transfer
+    pub allow_list_only: bool,         // Recipients must be on the allow-list
+    pub lockup_period: u64,            // Seconds after creation before a vault can be transferred
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub admin_approved: bool,
+    pub requested_at: u64,
+}
+
+// DataKey for guardian-based key recovery
+#[contracttype]
+pub enum RecoveryDataKey {
+    Guardians(u64), // GuardianConfig registered by a vault's owner
+    Pending(u64),   // RecoveryRequest in progress for a vault
+    Delay,          // Seconds between reaching the threshold and completing
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardianConfig {
+    pub guardians: Vec<Address>,
+    pub threshold: u32, // Guardian approvals needed to recover the vault
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryRequest {
+    pub new_owner: Address,
+    pub approvals: Vec<Address>,
+    pub initiated_at: u64,
+    pub threshold_reached_at: Option<u64>, // The delay runs from here
+}
+
+// DataKey for vesting acceleration
+#[contracttype]
+pub enum AccelerationDataKey {
+    Accelerated(u64),   // Tokens vested ahead of schedule for a vault
+    Rule(u64),          // AccelerationRule for a vault
+    ChangeOfControl,    // Timestamp of the recorded change-of-control event
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccelerationRule {
+    pub bps: u32,             // Share of the remaining unvested tokens to vest
+    pub double_trigger: bool, // Also requires a (non bad-leaver) termination after the event
+    pub window: u64,          // Double trigger: seconds after the event a termination counts (0 = no limit)
+    pub applied: bool,
 }
 
 #[contracttype]
@@ -43,6 +205,82 @@ pub struct Milestone {
     pub is_unlocked: bool,
 }
 
+// DataKey for scoped vault delegates
+#[contracttype]
+pub enum DelegateDataKey {
+    VaultDelegates(u64), // Map<Address, DelegateEntry>
+}
+
+// Actions a vault owner can grant to a delegate
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DelegateScope {
+    Claim,        // claim_as_delegate
+    Stake,        // stake_tokens / unstake_tokens / redelegate
+    SetAutoClaim, // auto_claim with the delegate as keeper
+    Rotate,       // rotate_beneficiary_key
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DelegateEntry {
+    pub scopes: Vec<DelegateScope>,
+    pub expires_at: u64,          // 0 = never expires
+    pub claim_cap: i128,          // Max tokens claimable per cap period (0 = uncapped)
+    pub cap_period: u64,          // Length of a cap period in seconds
+    pub period_start: u64,        // Start of the current cap period
+    pub claimed_in_period: i128,  // Tokens claimed by this delegate in the current period
+}
+
+// DataKey for claim payout routing
+#[contracttype]
+pub enum PayoutDataKey {
+    VaultSplits(u64), // Vec<PayoutSplit> applied to every claim of the vault
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutSplit {
+    pub recipient: Address,
+    pub bps: u32, // Share in basis points; a vault's splits sum to 10_000
+}
+
+// DataKey for staking state tracked outside the Vault struct
+#[contracttype]
+pub enum StakingDataKey {
+    Adapters,         // Map<Address, bool> of registered adapters (false = delisted)
+    DefaultAdapter,   // Adapter used by vaults that have not chosen one
+    VaultAdapter(u64), // Adapter chosen by (or pinned to) a vault
+    UnbondingPeriod,
+    VaultValidators(u64), // Map<Address, i128> of amount staked per validator
+    VaultUnbonding(u64),  // Vec<UnbondingEntry> of tokens waiting to become liquid
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct UnbondingEntry {
+    pub amount: i128,
+    pub release_time: u64, // Timestamp at which the tokens become claimable again
+}
+
+// DataKey for governance voting power derived from locked vault balances
+#[contracttype]
+pub enum VotingDataKey {
+    LockedBalance(Address), // Sum of unreleased tokens over all vaults owned by the address
+    VoteDelegate(Address),  // Address voting with the owner's locked balance (defaults to owner)
+    // Checkpoints live in persistent storage, one entry each, so an account's
+    // history never grows a single value or the contract instance
+    CheckpointCount(Address), // u32 number of checkpoints written for the address
+    Checkpoint(Address, u32), // VotingCheckpoint by index, ordered by ledger
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VotingCheckpoint {
+    pub ledger: u32,
+    pub power: i128,
+}
+
 #[contracttype]
 pub struct BatchCreateData {
     pub recipients: Vec<Address>,
@@ -50,26 +288,27 @@ pub struct BatchCreateData {
     pub start_times: Vec<u64>,
     pub end_times: Vec<u64>,
     pub keeper_fees: Vec<i128>,
-    pub step_durations: Vec<u64>,
+    pub step_durations: Vec<u64>, // May be empty for an all-linear batch
 }
 
+// One row of a batch, in the form create_vaults_* and validate_batch take
 #[contracttype]
-pub struct TokensRevoked {
-    pub vault_id: u64,
-    pub vested_amount: i128,
-    pub unvested_amount: i128,
-    pub beneficiary: Address,
-    pub timestamp: u64,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultSpec {
+    pub recipient: Address,
+    pub token: Address, // Must be whitelisted
+    pub amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub keeper_fee: i128,
+    pub step_duration: u64, // 0 = linear; otherwise must divide end_time - start_time
 }
 
 #[contracttype]
-pub struct VaultCreated {
-    pub vault_id: u64,
-    pub beneficiary: Address,
-    pub total_amount: i128,
-    pub cliff_duration: u64,
-    pub start_time: u64,
-    pub title: String,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchRowIssue {
+    pub index: u32,
+    pub error: Error, // A VestingError, as single creation would fail with it
 }
 
 #[contractimpl]
@@ -81,6 +320,8 @@ impl VestingContract {
             let mut whitelist: Map<Address, bool> = env.storage().instance().get(&WhitelistDataKey::WhitelistedTokens).unwrap_or(Map::new(&env));
             whitelist.set(token.clone(), true);
             env.storage().instance().set(&WhitelistDataKey::WhitelistedTokens, &whitelist);
+
+            TokenWhitelisted { token }.publish(&env);
         }
 
         // Check if token is whitelisted
@@ -109,6 +350,12 @@ impl VestingContract {
         // Initialize whitelisted tokens map
         let whitelist: Map<Address, bool> = Map::new(&env);
         env.storage().instance().set(&WhitelistDataKey::WhitelistedTokens, &whitelist);
+
+        ContractInitialized {
+            admin,
+            initial_supply,
+        }
+        .publish(&env);
     }
 
     // Helper function to check if caller is admin
@@ -162,6 +409,11 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::ProposedAdmin, &new_admin);
+
+        AdminProposed {
+            proposed_admin: new_admin,
+        }
+        .publish(&env);
     }
 
     // Accept admin ownership (second step of two-step process)
@@ -178,12 +430,23 @@ impl VestingContract {
         }
 
         // Transfer admin rights
+        let previous_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminAddress)
+            .unwrap_or_else(|| panic!("Admin not set"));
         env.storage()
             .instance()
             .set(&DataKey::AdminAddress, &proposed_admin);
 
         // Clear the proposed admin
         env.storage().instance().remove(&DataKey::ProposedAdmin);
+
+        AdminTransferred {
+            previous_admin,
+            new_admin: proposed_admin,
+        }
+        .publish(&env);
     }
 
     // Get current admin address
@@ -200,8 +463,19 @@ impl VestingContract {
     }
 
     // Full initialization - writes all metadata immediately
-
+    pub fn create_vault_full(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
         Self::require_admin(&env);
+        Self::require_valid_vault(&env, amount, start_time, end_time, keeper_fee, step_duration);
 
         // Get next vault ID
         let mut vault_count: u64 = env
@@ -243,6 +517,9 @@ impl VestingContract {
             is_transferable,
             step_duration,
             staked_amount: 0,
+            revocation_policy: RevocationPolicy::ForfeitUnvested,
+            suspended_at: None,
+            suspended_duration: 0,
         };
 
         // Store vault data immediately (expensive gas usage)
@@ -260,13 +537,14 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::UserVaults(owner.clone()), &user_vaults);
+        Self::adjust_locked_balance(&env, &owner, amount);
 
         // Update vault count
         env.storage()
             .instance()
             .set(&DataKey::VaultCount, &vault_count);
 
-        // Emit VaultCreated event with strictly typed fields
+        // Emit VaultCreated event
         let cliff_duration = start_time.saturating_sub(now);
         let vault_created = VaultCreated {
             vault_id: vault_count,
@@ -274,17 +552,33 @@ impl VestingContract {
             total_amount: amount,
             cliff_duration,
             start_time,
+            end_time,
+            step_duration: vault.step_duration,
+            keeper_fee: vault.keeper_fee,
+            is_irrevocable: vault.is_irrevocable,
+            is_transferable: vault.is_transferable,
+            is_initialized: vault.is_initialized,
             title: String::from_slice(&env, ""),
         };
-        env.events().publish(
-            (Symbol::new(&env, "VaultCreated"), vault_count),
-            vault_created,
-        );
+        vault_created.publish(&env);
 
         vault_count
     }
 
     // Lazy initialization - writes minimal data initially
+    pub fn create_vault_lazy(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        is_revocable: bool,
+        is_transferable: bool,
+        step_duration: u64,
+    ) -> u64 {
+        Self::require_admin(&env);
+        Self::require_valid_vault(&env, amount, start_time, end_time, keeper_fee, step_duration);
 
         // Get next vault ID
         let mut vault_count: u64 = env
@@ -326,6 +620,9 @@ impl VestingContract {
             is_transferable,
             step_duration,
             staked_amount: 0,
+            revocation_policy: RevocationPolicy::ForfeitUnvested,
+            suspended_at: None,
+            suspended_duration: 0,
         };
 
         // Store only essential data initially (cheaper gas)
@@ -338,9 +635,12 @@ impl VestingContract {
             .instance()
             .set(&DataKey::VaultCount, &vault_count);
 
-        // Don't update user vaults list yet (lazy)
+        // Index as pending rather than in the user vaults list (lazy), but the
+        // tokens still carry votes
+        Self::push_pending_vault(&env, &owner, vault_count);
+        Self::adjust_locked_balance(&env, &owner, amount);
 
-        // Emit VaultCreated event with strictly typed fields
+        // Emit VaultCreated event
         let cliff_duration = start_time.saturating_sub(now);
         let vault_created = VaultCreated {
             vault_id: vault_count,
@@ -348,17 +648,83 @@ impl VestingContract {
             total_amount: amount,
             cliff_duration,
             start_time,
+            end_time,
+            step_duration: vault.step_duration,
+            keeper_fee: vault.keeper_fee,
+            is_irrevocable: vault.is_irrevocable,
+            is_transferable: vault.is_transferable,
+            is_initialized: vault.is_initialized,
             title: String::from_slice(&env, ""),
         };
-        env.events().publish(
-            (Symbol::new(&env, "VaultCreated"), vault_count),
-            vault_created,
-        );
+        vault_created.publish(&env);
 
         vault_count
     }
 
+    // Parameters every vault must satisfy, so the vesting math can never
+    // divide by zero or overflow later. Single and batch creation both use it.
+    fn vault_error(
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        step_duration: u64,
+    ) -> Option<VestingError> {
+        if amount <= 0 {
+            return Some(VestingError::NonPositiveAmount);
+        }
+        if end_time <= start_time {
+            return Some(VestingError::EndNotAfterStart);
+        }
+        if keeper_fee < 0 {
+            return Some(VestingError::NegativeKeeperFee);
+        }
+        if keeper_fee > amount {
+            return Some(VestingError::KeeperFeeExceedsAmount);
+        }
+        let duration = end_time - start_time;
+        if step_duration > duration {
+            return Some(VestingError::StepExceedsDuration);
+        }
+        if amount.checked_mul(duration as i128).is_none() {
+            return Some(VestingError::AmountTooLarge);
+        }
+        None
+    }
+
+    fn require_valid_vault(
+        env: &Env,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        keeper_fee: i128,
+        step_duration: u64,
+    ) {
+        if let Some(error) = Self::vault_error(amount, start_time, end_time, keeper_fee, step_duration) {
+            panic_with_error!(env, error);
+        }
+    }
+
+    // Initialize many lazy vaults at once. Anyone may pay for this: it only
+    // moves each vault from its owner's pending index to UserVaults.
+    // Returns how many vaults were initialized by this call.
+    pub fn initialize_vaults(env: Env, vault_ids: Vec<u64>) -> u32 {
+        let mut initialized = 0;
+        for vault_id in vault_ids.iter() {
+            if Self::initialize_vault_metadata(&env, vault_id) {
+                initialized += 1;
+            }
+        }
+        initialized
+    }
+
     // Initialize vault metadata when needed (on-demand)
+    fn initialize_vault_metadata(env: &Env, vault_id: u64) -> bool {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
 
         // Only initialize if not already initialized
         if !vault.is_initialized {
@@ -369,6 +735,7 @@ impl VestingContract {
             env.storage()
                 .instance()
                 .set(&DataKey::VaultData(vault_id), &updated_vault);
+            Self::remove_pending_vault(env, &updated_vault.owner, vault_id);
 
             // Update user vaults list (deferred)
             let mut user_vaults: Vec<u64> = env
@@ -379,7 +746,13 @@ impl VestingContract {
             user_vaults.push_back(vault_id);
             env.storage()
                 .instance()
-                .set(&DataKey::UserVaults(updated_vault.owner), &user_vaults);
+                .set(&DataKey::UserVaults(updated_vault.owner.clone()), &user_vaults);
+
+            VaultInitialized {
+                vault_id,
+                owner: updated_vault.owner,
+            }
+            .publish(env);
 
             true
         } else {
@@ -387,32 +760,220 @@ impl VestingContract {
         }
     }
 
+    // Lazy vaults of `owner` that have not been initialized yet
+    fn pending_vaults(env: &Env, owner: &Address) -> Vec<u64> {
+        env.storage()
+            .instance()
+            .get(&LazyDataKey::PendingVaults(owner.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn push_pending_vault(env: &Env, owner: &Address, vault_id: u64) {
+        let mut pending = Self::pending_vaults(env, owner);
+        pending.push_back(vault_id);
+        env.storage()
+            .instance()
+            .set(&LazyDataKey::PendingVaults(owner.clone()), &pending);
+    }
+
+    fn remove_pending_vault(env: &Env, owner: &Address, vault_id: u64) {
+        let mut pending = Self::pending_vaults(env, owner);
+        if let Some(index) = pending.first_index_of(vault_id) {
+            pending.remove(index);
+        }
+        if pending.is_empty() {
+            env.storage()
+                .instance()
+                .remove(&LazyDataKey::PendingVaults(owner.clone()));
+        } else {
+            env.storage()
+                .instance()
+                .set(&LazyDataKey::PendingVaults(owner.clone()), &pending);
+        }
+    }
+
+    // Single source of truth for how much of a vault has vested at `now`.
+    // Every claim path and view goes through here: milestone vaults vest by
+    // unlocked percentage, all others by time (linear or step).
+    fn vested_amount(env: &Env, vault_id: u64, vault: &Vault, now: u64) -> i128 {
+        let milestones: Vec<Milestone> = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultMilestones(vault_id))
+            .unwrap_or(Vec::new(env));
+        let scheduled = if milestones.is_empty() {
+            Self::calculate_time_vested_amount(vault, now)
+        } else {
+            Self::unlocked_amount(vault.total_amount, Self::unlocked_percentage(&milestones))
+        };
+
+        // Accelerated tokens vest on top of the schedule, pulling its tail forward
+        let accelerated: i128 = env
+            .storage()
+            .instance()
+            .get(&AccelerationDataKey::Accelerated(vault_id))
+            .unwrap_or(0);
+        if scheduled + accelerated > vault.total_amount {
+            vault.total_amount
+        } else {
+            scheduled + accelerated
+        }
+    }
+
     // Helper to calculate vested amount based on time (linear or step)
-    fn calculate_time_vested_amount(env: &Env, vault: &Vault) -> i128 {
-        let now = env.ledger().timestamp();
+    fn calculate_time_vested_amount(vault: &Vault, now: u64) -> i128 {
         if now < vault.start_time {
             return 0;
         }
-        if now >= vault.end_time {
+        // Suspensions extend end_time, so compare the vesting clock against
+        // the unextended end
+        let now = Self::vesting_clock(vault, now);
+        let end_time = vault.end_time.saturating_sub(vault.suspended_duration);
+        if now >= end_time {
             return vault.total_amount;
         }
-        let duration = vault.end_time - vault.start_time;
+        let duration = end_time - vault.start_time;
         if duration == 0 {
             return vault.total_amount;
         }
         let elapsed = now - vault.start_time;
-        let effective_elapsed = if vault.step_duration > 0 {
-            (elapsed / vault.step_duration) * vault.step_duration
-        } else {
-            elapsed
+        let effective_elapsed = match elapsed.checked_div(vault.step_duration) {
+            Some(steps) => steps * vault.step_duration,
+            None => elapsed,
         };
-        
+
         // Use i128 math
         (vault.total_amount * effective_elapsed as i128) / duration as i128
     }
 
+    // Helper: `now` minus all time vesting was suspended after start_time
+    fn vesting_clock(vault: &Vault, now: u64) -> u64 {
+        let mut paused = vault.suspended_duration;
+        if let Some(suspended_at) = vault.suspended_at {
+            let from = if suspended_at > vault.start_time { suspended_at } else { vault.start_time };
+            paused += now.saturating_sub(from);
+        }
+        let clock = now.saturating_sub(paused);
+        if clock < vault.start_time && now >= vault.start_time {
+            vault.start_time
+        } else {
+            clock
+        }
+    }
+
     // Claim tokens from vault
     pub fn claim_tokens(env: Env, vault_id: u64, claim_amount: i128) -> i128 {
+        let vault = Self::internal_claim(&env, vault_id, claim_amount);
+        Self::distribute_payout(&env, vault_id, &vault.owner, claim_amount);
+
+        claim_amount
+    }
+
+    // Claim tokens from vault and pay them to `recipient` instead of the owner's payout splits
+    pub fn claim_to(env: Env, vault_id: u64, claim_amount: i128, recipient: Address) -> i128 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        // Redirecting funds requires the owner's signature
+        vault.owner.require_auth();
+
+        Self::internal_claim(&env, vault_id, claim_amount);
+        TokensPaid {
+            vault_id,
+            recipient,
+            amount: claim_amount,
+        }
+        .publish(&env);
+
+        claim_amount
+    }
+
+    // Claim everything currently claimable from a vault
+    pub fn claim_max(env: Env, vault_id: u64) -> i128 {
+        let claimable = Self::liquid_claimable_amount(&env, vault_id);
+        if claimable <= 0 {
+            panic!("No tokens available to claim");
+        }
+
+        let vault = Self::internal_claim(&env, vault_id, claimable);
+        Self::distribute_payout(&env, vault_id, &vault.owner, claimable);
+
+        claimable
+    }
+
+    // Claim everything currently claimable across all of `owner`'s vaults,
+    // paying each recipient a single aggregated amount
+    pub fn claim_all(env: Env, owner: Address) -> i128 {
+        owner.require_auth();
+
+        let vault_ids = Self::get_user_vaults(env.clone(), owner.clone());
+
+        let mut total_claimed: i128 = 0;
+        let mut vaults_claimed: u32 = 0;
+        let mut payouts: Map<Address, i128> = Map::new(&env);
+        for vault_id in vault_ids.iter() {
+            let claimable = Self::liquid_claimable_amount(&env, vault_id);
+            if claimable <= 0 {
+                continue;
+            }
+
+            Self::internal_claim(&env, vault_id, claimable);
+            for (recipient, share) in Self::payout_shares(&env, vault_id, &owner, claimable).iter() {
+                let current = payouts.get(recipient.clone()).unwrap_or(0);
+                payouts.set(recipient, current + share);
+            }
+
+            total_claimed += claimable;
+            vaults_claimed += 1;
+        }
+
+        if total_claimed == 0 {
+            panic!("No tokens available to claim");
+        }
+
+        for (recipient, amount) in payouts.iter() {
+            ClaimAllPaid {
+                owner: owner.clone(),
+                recipient,
+                amount,
+            }
+            .publish(&env);
+        }
+        ClaimAllSummary {
+            owner,
+            vaults_claimed,
+            total_claimed,
+        }
+        .publish(&env);
+
+        total_claimed
+    }
+
+    // Internal helper: the claimable amount capped at what a claim can pay out now.
+    // Unbonding tokens never count; staked ones only while claims may auto-unstake them.
+    fn liquid_claimable_amount(env: &Env, vault_id: u64) -> i128 {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let claimable = Self::get_claimable_amount(env.clone(), vault_id);
+        let mut liquid = vault.total_amount - vault.released_amount - Self::settle_unbonding(env, vault_id);
+        if Self::get_unbonding_period(env.clone()) > 0 {
+            liquid -= vault.staked_amount;
+        }
+        if claimable < liquid { claimable } else { liquid }
+    }
+
+    // Internal helper: release `claim_amount` from a vault after vesting and liquidity checks.
+    // Does NOT emit payout events — caller decides where the tokens go.
+    fn internal_claim(env: &Env, vault_id: u64, claim_amount: i128) -> Vault {
         let mut vault: Vault = env
             .storage()
             .instance()
@@ -421,39 +982,51 @@ impl VestingContract {
                 panic!("Vault not found");
             });
 
+        // A lazy vault is initialized by its first claim
         if !vault.is_initialized {
-            panic!("Vault not initialized");
+            Self::initialize_vault_metadata(env, vault_id);
+            vault.is_initialized = true;
         }
         if claim_amount <= 0 {
             panic!("Claim amount must be positive");
         }
 
-        // Check if milestones are configured
-        let unlocked_amount = if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
-            let milestones = Self::require_milestones_configured(&env, vault_id);
-            let unlocked_pct = Self::unlocked_percentage(&milestones);
-            Self::unlocked_amount(vault.total_amount, unlocked_pct)
-        } else {
-            // Fallback to time-based vesting
-            Self::calculate_time_vested_amount(&env, &vault)
-        };
+        let unlocked_amount =
+            Self::vested_amount(env, vault_id, &vault, env.ledger().timestamp());
 
         // Auto-unstake logic if needed
-        let liquid_balance = vault.total_amount - vault.released_amount - vault.staked_amount;
+        let unbonding = Self::settle_unbonding(env, vault_id);
+        let liquid_balance =
+            vault.total_amount - vault.released_amount - vault.staked_amount - unbonding;
         if claim_amount > liquid_balance {
             let deficit = claim_amount - liquid_balance;
-            
-            // Get staking contract
-            let staking_contract: Address = env.storage().instance()
-                .get(&Symbol::new(&env, "StakingContract"))
-                .expect("Staking contract not set");
 
-            // Call unstake on external contract
-            let args = vec![&env, vault_id.into_val(&env), deficit.into_val(&env)];
-            env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "unstake"), args);
+            // With an unbonding period the owner must unstake and wait before claiming
+            if Self::get_unbonding_period(env.clone()) > 0 || deficit > vault.staked_amount {
+                panic!("Staked tokens must finish unbonding before claiming");
+            }
+
+            // Unstake the deficit from the vault's adapter, one validator at a time
+            let adapter_client = StakingAdapterClient::new(env, &Self::vault_adapter(env, vault_id));
+            let mut remaining = deficit;
+            for (validator, staked) in Self::get_vault_validators(env.clone(), vault_id).iter() {
+                if remaining == 0 {
+                    break;
+                }
+                let taken = if staked < remaining { staked } else { remaining };
+                Self::release_validator_stake(env, vault_id, &validator, taken);
+                adapter_client.unstake(&vault_id, &taken, &validator);
+                remaining -= taken;
+            }
 
             // Update local state
             vault.staked_amount -= deficit;
+            TokensUnstaked {
+                vault_id,
+                amount: deficit,
+                release_time: env.ledger().timestamp(),
+            }
+            .publish(env);
             // Note: We don't save vault here yet, it's saved at the end of function
         }
 
@@ -470,65 +1043,131 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::VaultData(vault_id), &vault);
+        Self::adjust_locked_balance(env, &vault.owner, -claim_amount);
 
-        claim_amount
-    }
+        TokensClaimed {
+            vault_id,
+            amount: claim_amount,
+            released_amount: vault.released_amount,
+        }
+        .publish(env);
 
-    /// Transfers the beneficiary role of a vault to a new address.
-    /// Only the admin can perform this action (e.g., in case of lost keys).
-    pub fn transfer_beneficiary(env: Env, vault_id: u64, new_address: Address) {
-        Self::require_admin(&env);
+        vault
+    }
 
-        let mut vault: Vault = env
+    // Set standing payout splits for a vault (empty to pay the owner directly)
+    pub fn set_payout_splits(env: Env, vault_id: u64, splits: Vec<PayoutSplit>) {
+        let vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| panic!("Vault not found"));
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
 
-        let old_owner = vault.owner.clone();
+        // Redirecting funds requires the owner's signature
+        vault.owner.require_auth();
 
-        // Update user vaults index if the vault has been initialized
-        if vault.is_initialized {
-            // Remove vault_id from old owner's list
-            let old_vaults: Vec<u64> = env
-                .storage()
+        if splits.is_empty() {
+            env.storage()
                 .instance()
-                .get(&DataKey::UserVaults(old_owner.clone()))
-                .unwrap_or(Vec::new(&env));
-
-            let mut updated_old_vaults = Vec::new(&env);
-            for id in old_vaults.iter() {
-                if id != vault_id {
-                    updated_old_vaults.push_back(id);
+                .remove(&PayoutDataKey::VaultSplits(vault_id));
+        } else {
+            if splits.len() > MAX_PAYOUT_SPLITS {
+                panic!("Too many payout splits");
+            }
+            let mut total_bps: u32 = 0;
+            for split in splits.iter() {
+                if split.bps == 0 {
+                    panic!("Split share must be positive");
                 }
+                total_bps = total_bps.saturating_add(split.bps);
+            }
+            if total_bps != BPS_DENOMINATOR {
+                panic!("Split shares must sum to 10000 bps");
             }
             env.storage()
                 .instance()
-                .set(&DataKey::UserVaults(old_owner.clone()), &updated_old_vaults);
-
-            // Add vault_id to new owner's list
-            let mut new_vaults: Vec<u64> = env
-                .storage()
-                .instance()
-                .get(&DataKey::UserVaults(new_address.clone()))
-                .unwrap_or(Vec::new(&env));
-            new_vaults.push_back(vault_id);
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(new_address.clone()), &new_vaults);
+                .set(&PayoutDataKey::VaultSplits(vault_id), &splits);
         }
 
-        // Update vault owner
-        vault.owner = new_address.clone();
+        PayoutSplitsSet { vault_id, splits }.publish(&env);
+    }
+
+    pub fn get_payout_splits(env: Env, vault_id: u64) -> Vec<PayoutSplit> {
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .get(&PayoutDataKey::VaultSplits(vault_id))
+            .unwrap_or(Vec::new(&env))
+    }
 
-        // Emit BeneficiaryUpdated event
-        env.events().publish(
-            (Symbol::new(&env, "BeneficiaryUpdated"), vault_id),
-            (old_owner.clone(), new_address),
-        );
+    // Internal helper: emit a TokensPaid event per payout recipient of a claim
+    fn distribute_payout(env: &Env, vault_id: u64, owner: &Address, amount: i128) {
+        for (recipient, share) in Self::payout_shares(env, vault_id, owner, amount).iter() {
+            TokensPaid {
+                vault_id,
+                recipient,
+                amount: share,
+            }
+            .publish(env);
+        }
+    }
+
+    // Internal helper: split a claim between the vault's payout recipients.
+    // Rounding dust goes to the last split so the shares always sum to `amount`.
+    fn payout_shares(env: &Env, vault_id: u64, owner: &Address, amount: i128) -> Map<Address, i128> {
+        let mut shares = Map::new(env);
+        let splits = Self::get_payout_splits(env.clone(), vault_id);
+        if splits.is_empty() {
+            shares.set(owner.clone(), amount);
+            return shares;
+        }
+
+        let mut remaining = amount;
+        let last = splits.len() - 1;
+        for (i, split) in splits.iter().enumerate() {
+            let share = if i as u32 == last {
+                remaining
+            } else {
+                amount * split.bps as i128 / BPS_DENOMINATOR as i128
+            };
+            remaining -= share;
+            let current = shares.get(split.recipient.clone()).unwrap_or(0);
+            shares.set(split.recipient, current + share);
+        }
+        shares
+    }
+
+    /// Transfers the beneficiary role of a vault to a new address.
+    /// Only the admin can perform this action (e.g., in case of lost keys).
+    pub fn transfer_beneficiary(env: Env, vault_id: u64, new_address: Address) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        // Beneficiaries of irrevocable vaults who registered guardians recover
+        // through them, not through the admin
+        if vault.is_irrevocable && env.storage().instance().has(&RecoveryDataKey::Guardians(vault_id)) {
+            panic!("Vault is recovered through its guardians");
+        }
+
+        // Approvals, pending transfers and delegates were granted by the old
+        // owner and must not survive the reassignment
+        let old_owner = vault.owner.clone();
+        Self::reassign_owner(&env, vault_id, &mut vault, &new_address);
+
+        // Emit VaultTransferred event
+        VaultTransferred {
+            vault_id,
+            from: old_owner.clone(),
+            to: new_address,
+            kind: TransferKind::Admin,
+        }
+        .publish(&env);
     }
 
     // Set delegate address for a vault (only owner can call)
@@ -560,14 +1199,18 @@ impl VestingContract {
             .set(&DataKey::VaultData(vault_id), &vault);
 
         // Emit DelegateUpdated event
-        env.events().publish(
-            (Symbol::new(&env, "DelegateUpdated"), vault_id),
-            (old_delegate, delegate),
-        );
+        DelegateUpdated {
+            vault_id,
+            old_delegate,
+            new_delegate: delegate,
+        }
+        .publish(&env);
     }
 
     // Claim tokens as delegate (tokens still go to owner)
-    pub fn claim_as_delegate(env: Env, vault_id: u64, claim_amount: i128) -> i128 {
+    pub fn claim_as_delegate(env: Env, vault_id: u64, delegate: Address, claim_amount: i128) -> i128 {
+        delegate.require_auth();
+
         let vault: Vault = env
             .storage()
             .instance()
@@ -576,39 +1219,171 @@ impl VestingContract {
                 panic!("Vault not found");
             });
 
-        if !vault.is_initialized {
-            panic!("Vault not initialized");
-        }
         if claim_amount <= 0 {
             panic!("Claim amount must be positive");
         }
 
-        // Check if caller is authorized delegate
+        // Check if caller is authorized delegate. The legacy single delegate
+        // keeps unrestricted claim rights; scoped delegates are capped.
+        if vault.delegate != Some(delegate.clone()) {
+            Self::use_delegate_claim_allowance(&env, vault_id, &delegate, claim_amount);
+        }
+
+        // Same vesting and liquidity rules as a regular claim
+        Self::internal_claim(&env, vault_id, claim_amount);
+        Self::distribute_payout(&env, vault_id, &vault.owner, claim_amount);
+
+        claim_amount // Tokens go to original owner, not delegate
+    }
+
+    // Add or replace a scoped delegate for a vault (only owner can call)
+    pub fn add_delegate(
+        env: Env,
+        vault_id: u64,
+        delegate: Address,
+        scopes: Vec<DelegateScope>,
+        expires_at: u64,
+        claim_cap: i128,
+        cap_period: u64,
+    ) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
         let caller = env.current_contract_address();
-        let delegate = vault.delegate.clone();
-        if !(delegate.is_some() && caller == delegate.unwrap()) {
-            panic!("Caller is not authorized delegate for this vault");
+        if caller != vault.owner {
+            panic!("Only vault owner can set delegate");
         }
 
-        let milestones = Self::require_milestones_configured(&env, vault_id);
-        let unlocked_pct = Self::unlocked_percentage(&milestones);
-        let unlocked_amount = Self::unlocked_amount(vault.total_amount, unlocked_pct);
-        let available_to_claim = unlocked_amount - vault.released_amount;
-        if available_to_claim <= 0 {
-            panic!("No tokens available to claim");
+        let now = env.ledger().timestamp();
+        if scopes.is_empty() {
+            panic!("At least one scope required");
         }
-        if claim_amount > available_to_claim {
-            panic!("Insufficient unlocked tokens to claim");
+        if expires_at != 0 && expires_at <= now {
+            panic!("Expiry must be in the future");
+        }
+        if claim_cap < 0 {
+            panic!("Claim cap cannot be negative");
+        }
+        if claim_cap > 0 && cap_period == 0 {
+            panic!("Cap period required for claim cap");
         }
 
-        // Update vault (same as regular claim)
-        let mut updated_vault = vault.clone();
-        updated_vault.released_amount += claim_amount;
+        let mut delegates = Self::get_delegates(env.clone(), vault_id);
+        if !delegates.contains_key(delegate.clone()) && delegates.len() >= MAX_DELEGATES {
+            panic!("Too many delegates");
+        }
+        delegates.set(
+            delegate.clone(),
+            DelegateEntry {
+                scopes: scopes.clone(),
+                expires_at,
+                claim_cap,
+                cap_period,
+                period_start: now,
+                claimed_in_period: 0,
+            },
+        );
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &updated_vault);
+            .set(&DelegateDataKey::VaultDelegates(vault_id), &delegates);
+
+        DelegateAdded {
+            vault_id,
+            delegate,
+            scopes,
+            expires_at,
+            claim_cap,
+            cap_period,
+        }
+        .publish(&env);
+    }
+
+    // Remove a scoped delegate from a vault (only owner can call)
+    pub fn remove_delegate(env: Env, vault_id: u64, delegate: Address) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
 
-        claim_amount // Tokens go to original owner, not delegate
+        let caller = env.current_contract_address();
+        if caller != vault.owner {
+            panic!("Only vault owner can set delegate");
+        }
+
+        let mut delegates = Self::get_delegates(env.clone(), vault_id);
+        if delegates.remove(delegate.clone()).is_none() {
+            panic!("Delegate not found");
+        }
+        env.storage()
+            .instance()
+            .set(&DelegateDataKey::VaultDelegates(vault_id), &delegates);
+
+        DelegateRemoved { vault_id, delegate }.publish(&env);
+    }
+
+    // Get the scoped delegates of a vault
+    pub fn get_delegates(env: Env, vault_id: u64) -> Map<Address, DelegateEntry> {
+        env.storage()
+            .instance()
+            .get(&DelegateDataKey::VaultDelegates(vault_id))
+            .unwrap_or(Map::new(&env))
+    }
+
+    // Internal helper: panic unless `caller` is an unexpired delegate holding `scope`
+    fn require_delegate_scope(
+        env: &Env,
+        vault_id: u64,
+        caller: &Address,
+        scope: DelegateScope,
+    ) -> DelegateEntry {
+        let entry = Self::get_delegates(env.clone(), vault_id)
+            .get(caller.clone())
+            .unwrap_or_else(|| panic!("Caller is not authorized delegate for this vault"));
+        if entry.expires_at != 0 && env.ledger().timestamp() >= entry.expires_at {
+            panic!("Delegate permission expired");
+        }
+        if !entry.scopes.contains(scope) {
+            panic!("Delegate lacks required scope");
+        }
+        entry
+    }
+
+    // Internal helper: check the Claim scope and charge `amount` against the delegate's period cap
+    fn use_delegate_claim_allowance(env: &Env, vault_id: u64, caller: &Address, amount: i128) {
+        let mut entry = Self::require_delegate_scope(env, vault_id, caller, DelegateScope::Claim);
+        if entry.claim_cap == 0 {
+            return;
+        }
+
+        // Roll over to a fresh period once the current one has elapsed
+        let now = env.ledger().timestamp();
+        if now >= entry.period_start + entry.cap_period {
+            let elapsed_periods = (now - entry.period_start) / entry.cap_period;
+            entry.period_start += elapsed_periods * entry.cap_period;
+            entry.claimed_in_period = 0;
+        }
+        if entry.claimed_in_period + amount > entry.claim_cap {
+            panic!("Delegate claim cap exceeded");
+        }
+        entry.claimed_in_period += amount;
+
+        let mut delegates = Self::get_delegates(env.clone(), vault_id);
+        delegates.set(caller.clone(), entry);
+        env.storage()
+            .instance()
+            .set(&DelegateDataKey::VaultDelegates(vault_id), &delegates);
     }
 
     pub fn set_milestones(env: Env, vault_id: u64, milestones: Vec<Milestone>) {
@@ -649,10 +1424,12 @@ impl VestingContract {
         env.storage()
             .instance()
             .set(&DataKey::VaultMilestones(vault_id), &milestones);
-        env.events().publish(
-            (Symbol::new(&env, "MilestonesSet"), vault_id),
-            (milestones.len(), total_pct),
-        );
+        MilestonesSet {
+            vault_id,
+            count: milestones.len(),
+            total_percentage: total_pct,
+        }
+        .publish(&env);
     }
 
     pub fn get_milestones(env: Env, vault_id: u64) -> Vec<Milestone> {
@@ -698,10 +1475,12 @@ impl VestingContract {
             .instance()
             .set(&DataKey::VaultMilestones(vault_id), &updated);
         let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(&env, "MilestoneUnlocked"), vault_id),
-            (milestone_id, timestamp),
-        );
+        MilestoneUnlocked {
+            vault_id,
+            milestone_id,
+            timestamp,
+        }
+        .publish(&env);
     }
 
     // Admin-only: set a short title for a vault (max 32 bytes)
@@ -719,118 +1498,163 @@ impl VestingContract {
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        vault.title = title;
+        vault.title = title.clone();
         env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+
+        VaultTitleSet { vault_id, title }.publish(&env);
     }
 
     // Batch create vaults with lazy initialization
     pub fn batch_create_vaults_lazy(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
         Self::require_admin(&env);
+        Self::require_valid_batch(&env, &batch_data, None);
+        Self::create_batch(&env, &batch_data, true)
+    }
 
-        let mut vault_ids = Vec::new(&env);
-        let initial_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::VaultCount)
-            .unwrap_or(0);
+    // Batch create vaults with full initialization
+    pub fn batch_create_vaults_full(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
+        Self::require_admin(&env);
+        Self::require_valid_batch(&env, &batch_data, None);
+        Self::create_batch(&env, &batch_data, false)
+    }
 
-        // Check total admin balance
-        let total_amount: i128 = batch_data.amounts.iter().sum();
-        let mut admin_balance: i128 = env
+    // Same as batch_create_vaults_lazy, one VaultSpec per vault
+    pub fn create_vaults_lazy(env: Env, specs: Vec<VaultSpec>) -> Vec<u64> {
+        Self::require_admin(&env);
+        let (batch_data, tokens) = Self::split_specs(&env, &specs);
+        Self::require_valid_batch(&env, &batch_data, Some(&tokens));
+        Self::create_batch(&env, &batch_data, true)
+    }
+
+    // Same as batch_create_vaults_full, one VaultSpec per vault
+    pub fn create_vaults_full(env: Env, specs: Vec<VaultSpec>) -> Vec<u64> {
+        Self::require_admin(&env);
+        let (batch_data, tokens) = Self::split_specs(&env, &specs);
+        Self::require_valid_batch(&env, &batch_data, Some(&tokens));
+        Self::create_batch(&env, &batch_data, false)
+    }
+
+    // Dry run of create_vaults_*: every problem with every row, against the
+    // current whitelist and admin balance. Empty means the batch would succeed.
+    pub fn validate_batch(env: Env, specs: Vec<VaultSpec>) -> Vec<BatchRowIssue> {
+        let (batch_data, tokens) = Self::split_specs(&env, &specs);
+        Self::batch_issues(&env, &batch_data, Some(&tokens))
+    }
+
+    fn split_specs(env: &Env, specs: &Vec<VaultSpec>) -> (BatchCreateData, Vec<Address>) {
+        let mut batch_data = BatchCreateData {
+            recipients: Vec::new(env),
+            amounts: Vec::new(env),
+            start_times: Vec::new(env),
+            end_times: Vec::new(env),
+            keeper_fees: Vec::new(env),
+            step_durations: Vec::new(env),
+        };
+        let mut tokens = Vec::new(env);
+        for spec in specs.iter() {
+            batch_data.recipients.push_back(spec.recipient);
+            batch_data.amounts.push_back(spec.amount);
+            batch_data.start_times.push_back(spec.start_time);
+            batch_data.end_times.push_back(spec.end_time);
+            batch_data.keeper_fees.push_back(spec.keeper_fee);
+            batch_data.step_durations.push_back(spec.step_duration);
+            tokens.push_back(spec.token);
+        }
+        (batch_data, tokens)
+    }
+
+    // Validate the whole batch before anything is written
+    fn require_valid_batch(env: &Env, batch_data: &BatchCreateData, tokens: Option<&Vec<Address>>) {
+        let len = batch_data.recipients.len();
+        if batch_data.amounts.len() != len
+            || batch_data.start_times.len() != len
+            || batch_data.end_times.len() != len
+            || batch_data.keeper_fees.len() != len
+            || (batch_data.step_durations.len() != len && !batch_data.step_durations.is_empty())
+        {
+            panic!("Batch vectors must have the same length");
+        }
+        if !Self::batch_issues(env, batch_data, tokens).is_empty() {
+            panic!("Invalid batch; validate_batch lists the rows");
+        }
+    }
+
+    // Per-row problems with a batch. Only the VaultSpec form names tokens.
+    fn batch_issues(
+        env: &Env,
+        batch_data: &BatchCreateData,
+        tokens: Option<&Vec<Address>>,
+    ) -> Vec<BatchRowIssue> {
+        let admin_balance: i128 = env
             .storage()
             .instance()
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
-        if admin_balance < total_amount {
-            panic!("Insufficient admin balance for batch");
-        }
-        admin_balance -= total_amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
-
-        let now = env.ledger().timestamp();
+        let mut issues = Vec::new(env);
+        let mut total: i128 = 0;
         for i in 0..batch_data.recipients.len() {
-            let vault_id = initial_count + i as u64 + 1;
-
-            // Create vault with lazy initialization
-            let vault = Vault {
-                owner: batch_data.recipients.get(i).unwrap(),
-                delegate: None, // No delegate initially
-                total_amount: batch_data.amounts.get(i).unwrap(),
-                released_amount: 0,
-                start_time: batch_data.start_times.get(i).unwrap(),
-                end_time: batch_data.end_times.get(i).unwrap(),
-                keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
-                title: String::from_slice(&env, ""),
-                is_initialized: false, // Lazy initialization
-                is_irrevocable: false, // Default to revocable for batch operations
-                creation_time: now,
-                is_transferable: false, // Default to non-transferable for batch
-                step_duration: batch_data.step_durations.get(i).unwrap_or(0),
-                staked_amount: 0,
-            };
-
-            // Store vault data (minimal writes)
-            env.storage()
-                .instance()
-                .set(&DataKey::VaultData(vault_id), &vault);
-            vault_ids.push_back(vault_id);
-            // Emit VaultCreated event for each created vault
+            let mut report = |error: VestingError| issues.push_back(BatchRowIssue { index: i, error: error.into() });
+            let amount = batch_data.amounts.get(i).unwrap();
             let start_time = batch_data.start_times.get(i).unwrap();
-            let cliff_duration = start_time.saturating_sub(now);
-            let vault_created = VaultCreated {
-                vault_id,
-                beneficiary: vault.owner.clone(),
-                total_amount: vault.total_amount,
-                cliff_duration,
-                start_time,
-                title: String::from_slice(&env, ""),
-            };
-            env.events()
-                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
+            let end_time = batch_data.end_times.get(i).unwrap();
+            let step_duration = batch_data.step_durations.get(i).unwrap_or(0);
+
+            let keeper_fee = batch_data.keeper_fees.get(i).unwrap();
+            if let Some(error) = Self::vault_error(amount, start_time, end_time, keeper_fee, step_duration) {
+                report(error);
+            } else if step_duration > 0 && !(end_time - start_time).is_multiple_of(step_duration) {
+                report(VestingError::StepDoesNotDivideDuration);
+            }
+            if let Some(tokens) = tokens {
+                if !Self::is_token_whitelisted(env, &tokens.get(i).unwrap()) {
+                    report(VestingError::TokenNotWhitelisted);
+                }
+            }
+            let recipient = batch_data.recipients.get(i).unwrap();
+            let duplicate = (0..i).any(|j| {
+                batch_data.recipients.get(j).unwrap() == recipient
+                    && batch_data.amounts.get(j).unwrap() == amount
+                    && batch_data.start_times.get(j).unwrap() == start_time
+                    && batch_data.end_times.get(j).unwrap() == end_time
+                    && batch_data.step_durations.get(j).unwrap_or(0) == step_duration
+            });
+            if duplicate {
+                report(VestingError::DuplicateRow);
+            }
+            if amount > 0 {
+                total = total.saturating_add(amount);
+                if total > admin_balance {
+                    report(VestingError::InsufficientBalance);
+                }
+            }
         }
-
-        // Update vault count once (cheaper than individual updates)
-        let final_count = initial_count + batch_data.recipients.len() as u64;
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultCount, &final_count);
-
-        vault_ids
+        issues
     }
 
-    // Batch create vaults with full initialization
-    pub fn batch_create_vaults_full(env: Env, batch_data: BatchCreateData) -> Vec<u64> {
-        Self::require_admin(&env);
-
-        let mut vault_ids = Vec::new(&env);
+    // Write a validated batch. Lazy vaults go to the pending index instead of
+    // UserVaults until they are initialized.
+    fn create_batch(env: &Env, batch_data: &BatchCreateData, lazy: bool) -> Vec<u64> {
+        let mut vault_ids = Vec::new(env);
         let initial_count: u64 = env
             .storage()
             .instance()
             .get(&DataKey::VaultCount)
             .unwrap_or(0);
 
-        // Check total admin balance
         let total_amount: i128 = batch_data.amounts.iter().sum();
-        let mut admin_balance: i128 = env
+        let admin_balance: i128 = env
             .storage()
             .instance()
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
-        if admin_balance < total_amount {
-            panic!("Insufficient admin balance for batch");
-        }
-        admin_balance -= total_amount;
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+            .set(&DataKey::AdminBalance, &(admin_balance - total_amount));
 
         let now = env.ledger().timestamp();
         for i in 0..batch_data.recipients.len() {
             let vault_id = initial_count + i as u64 + 1;
 
-            // Create vault with full initialization
             let vault = Vault {
                 owner: batch_data.recipients.get(i).unwrap(),
                 delegate: None, // No delegate initially
@@ -839,48 +1663,56 @@ impl VestingContract {
                 start_time: batch_data.start_times.get(i).unwrap(),
                 end_time: batch_data.end_times.get(i).unwrap(),
                 keeper_fee: batch_data.keeper_fees.get(i).unwrap(),
-                title: String::from_slice(&env, ""),
-                is_initialized: true,
+                title: String::from_slice(env, ""),
+                is_initialized: !lazy,
                 is_irrevocable: false, // Default to revocable for batch operations
                 creation_time: now,
                 is_transferable: false, // Default to non-transferable for batch
                 step_duration: batch_data.step_durations.get(i).unwrap_or(0),
                 staked_amount: 0,
+                revocation_policy: RevocationPolicy::ForfeitUnvested,
+                suspended_at: None,
+                suspended_duration: 0,
             };
 
-            // Store vault data (expensive writes)
             env.storage()
                 .instance()
                 .set(&DataKey::VaultData(vault_id), &vault);
 
-            // Update user vaults list for each vault (expensive)
-            let mut user_vaults: Vec<u64> = env
-                .storage()
-                .instance()
-                .get(&DataKey::UserVaults(vault.owner.clone()))
-                .unwrap_or(Vec::new(&env));
-            user_vaults.push_back(vault_id);
-            env.storage()
-                .instance()
-                .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
+            if !lazy {
+                let mut user_vaults: Vec<u64> = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::UserVaults(vault.owner.clone()))
+                    .unwrap_or(Vec::new(env));
+                user_vaults.push_back(vault_id);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
+            } else {
+                Self::push_pending_vault(env, &vault.owner, vault_id);
+            }
+            Self::adjust_locked_balance(env, &vault.owner, vault.total_amount);
 
             vault_ids.push_back(vault_id);
-            // Emit VaultCreated event for each created vault
-            let start_time = batch_data.start_times.get(i).unwrap();
-            let cliff_duration = start_time.saturating_sub(now);
-            let vault_created = VaultCreated {
+            VaultCreated {
                 vault_id,
                 beneficiary: vault.owner.clone(),
                 total_amount: vault.total_amount,
-                cliff_duration,
-                start_time,
-                title: String::from_slice(&env, ""),
-            };
-            env.events()
-                .publish((Symbol::new(&env, "VaultCreated"), vault_id), vault_created);
+                cliff_duration: vault.start_time.saturating_sub(now),
+                start_time: vault.start_time,
+                end_time: vault.end_time,
+                step_duration: vault.step_duration,
+                keeper_fee: vault.keeper_fee,
+                is_irrevocable: vault.is_irrevocable,
+                is_transferable: vault.is_transferable,
+                is_initialized: vault.is_initialized,
+                title: String::from_slice(env, ""),
+            }
+            .publish(env);
         }
 
-        // Update vault count once
+        // Update vault count once (cheaper than individual updates)
         let final_count = initial_count + batch_data.recipients.len() as u64;
         env.storage()
             .instance()
@@ -889,40 +1721,24 @@ impl VestingContract {
         vault_ids
     }
 
-    // Get vault info (initializes if needed)
+    // Get vault info. Read-only: a lazy vault is returned with
+    // is_initialized = false until initialize_vaults or a claim touches it.
     pub fn get_vault(env: Env, vault_id: u64) -> Vault {
-
-        // Auto-initialize if lazy
-        if !vault.is_initialized {
-            Self::initialize_vault_metadata(&env, vault_id);
-            // Get updated vault
-            env.storage()
-                .instance()
-                .get(&DataKey::VaultData(vault_id))
-                .unwrap()
-        } else {
-            vault
-        }
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"))
     }
 
-    // Get user vaults (initializes all if needed)
+    // Get user vaults. Read-only: lazy vaults still awaiting initialization
+    // come from the pending index, after the initialized ones.
     pub fn get_user_vaults(env: Env, user: Address) -> Vec<u64> {
-        let vault_ids: Vec<u64> = env
+        let mut vault_ids: Vec<u64> = env
             .storage()
             .instance()
             .get(&DataKey::UserVaults(user.clone()))
             .unwrap_or(Vec::new(&env));
-
-        // Initialize all lazy vaults for this user
-        for vault_id in vault_ids.iter() {
-
-                });
-
-            if !vault.is_initialized {
-                Self::initialize_vault_metadata(&env, vault_id);
-            }
-        }
-
+        vault_ids.append(&Self::pending_vaults(&env, &user));
         vault_ids
     }
 
@@ -930,77 +1746,245 @@ impl VestingContract {
     // Internal helper: revoke full unreleased amount from a vault and emit event.
     // Does NOT update admin balance — caller is responsible for a single aggregated transfer.
     fn internal_revoke_full(env: &Env, vault_id: u64) -> i128 {
+        let unvested_amount = Self::internal_revoke_at(env, vault_id, env.ledger().timestamp());
+        if unvested_amount <= 0 {
+            panic!("No tokens available to revoke");
+        }
+
+        unvested_amount
+    }
+
+    // Internal helper: stop vesting at `at` and forfeit whatever is unvested by
+    // then. Returns 0 (and changes nothing) if the vault is fully vested at `at`.
+    fn internal_revoke_at(env: &Env, vault_id: u64, at: u64) -> i128 {
         let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
+        // Vested-but-unclaimed tokens stay with the beneficiary; only the
+        // unvested remainder goes back to the admin
+        let timestamp = env.ledger().timestamp();
+        let unvested_amount = Self::freeze_schedule(env, vault_id, &mut vault, at);
+        if unvested_amount <= 0 {
+            return 0;
+        }
         if vault.is_irrevocable {
             panic!("Vault is irrevocable");
         }
 
-        let unreleased_amount = vault.total_amount - vault.released_amount;
-        if unreleased_amount <= 0 {
-            panic!("No tokens available to revoke");
-        }
-
-        vault.released_amount = vault.total_amount;
         env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        Self::adjust_locked_balance(env, &vault.owner, -unvested_amount);
+        Self::publish_schedule_shortened(env, vault_id, &vault);
+
+        TokensRevoked {
+            vault_id,
+            vested_amount: vault.total_amount,
+            unvested_amount,
+            beneficiary: vault.owner.clone(),
+            timestamp,
+        }
+        .publish(env);
 
-        let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(env, "TokensRevoked"), vault_id),
-            (unreleased_amount, timestamp),
-        );
-
-        unreleased_amount
+        unvested_amount
     }
 
-    // Admin-only: Revoke tokens from a vault and return them to admin
-    pub fn revoke_tokens(env: Env, vault_id: u64) -> i128 {
-        Self::require_admin(&env);
-
-        let returned = Self::internal_revoke_full(&env, vault_id);
+    // Internal helper: stop vesting at `at`. The vault keeps exactly what had
+    // vested by then (never less than what was already claimed); until `at` it
+    // keeps vesting at the original rate, accelerated tokens included. Returns
+    // the unvested amount taken out of the vault, leaving the vault untouched
+    // when that is zero.
+    fn freeze_schedule(env: &Env, vault_id: u64, vault: &mut Vault, at: u64) -> i128 {
+        let mut vested = Self::vested_amount(env, vault_id, vault, at);
+        if vested < vault.released_amount {
+            vested = vault.released_amount;
+        }
+        let unvested = vault.total_amount - vested;
+        if unvested <= 0 {
+            return 0;
+        }
+        Self::require_revocable_liquidity(env, vault_id, vault, unvested);
 
-        // Single admin balance update for this call
-        let mut admin_balance: i128 = env
+        if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
+            // The unlocked amount is fully vested; milestones no longer apply
+            env.storage()
+                .instance()
+                .remove(&DataKey::VaultMilestones(vault_id));
+            vault.end_time = vault.start_time;
+        } else if at <= vault.start_time {
+            vault.end_time = vault.start_time;
+        } else {
+            // Ending on the last completed step keeps the slope of both linear
+            // and step schedules, so nothing vests faster or slower than before
+            let mut elapsed = Self::vesting_clock(vault, at) - vault.start_time;
+            if let Some(steps) = elapsed.checked_div(vault.step_duration) {
+                elapsed = steps * vault.step_duration;
+            }
+            // Accelerated tokens still vest on top of the frozen schedule, so
+            // it only has to cover the rest by `at`. Stretch it by the same
+            // share to keep the slope; the cap at the new total stops it there.
+            let accelerated: i128 = env
+                .storage()
+                .instance()
+                .get(&AccelerationDataKey::Accelerated(vault_id))
+                .unwrap_or(0);
+            if accelerated > 0 {
+                let scheduled = vested - accelerated;
+                elapsed = if scheduled > 0 {
+                    ((elapsed as i128 * vested + scheduled - 1) / scheduled) as u64
+                } else {
+                    0
+                };
+            }
+            vault.end_time = vault.start_time + vault.suspended_duration + elapsed;
+        }
+        vault.total_amount = vested;
+
+        unvested
+    }
+
+    // Admin-only: set what terminate() does for a vault
+    pub fn set_revocation_policy(env: Env, vault_id: u64, policy: RevocationPolicy) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
             .storage()
             .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        admin_balance += returned;
-        env.storage()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.revocation_policy = policy;
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+
+        RevocationPolicySet { vault_id, policy }.publish(&env);
+    }
+
+    // Admin-only: end a beneficiary's vesting as of `effective_time`, applying
+    // the vault's revocation policy. Returns the amount forfeited to the admin.
+    pub fn terminate(env: Env, vault_id: u64, reason: TerminationReason, effective_time: u64) -> i128 {
+        Self::require_admin(&env);
+
+        let vault: Vault = env
+            .storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
 
-        returned
+        // Double-trigger acceleration: the contract-level event already happened
+        // and this termination falls inside the rule's window
+        if reason != TerminationReason::BadLeaver {
+            if let (Some(rule), Some(event_time)) = (
+                env.storage()
+                    .instance()
+                    .get::<_, AccelerationRule>(&AccelerationDataKey::Rule(vault_id)),
+                env.storage()
+                    .instance()
+                    .get::<_, u64>(&AccelerationDataKey::ChangeOfControl),
+            ) {
+                let in_window = effective_time >= event_time
+                    && (rule.window == 0 || effective_time <= event_time + rule.window);
+                if rule.double_trigger && !rule.applied && in_window {
+                    Self::apply_acceleration_rule(&env, vault_id, rule);
+                }
+            }
+        }
+
+        let forfeited = match (vault.revocation_policy, reason) {
+            (RevocationPolicy::LeaverTerms(extension), TerminationReason::GoodLeaver) => {
+                Self::internal_revoke_at(&env, vault_id, effective_time.saturating_add(extension))
+            }
+            (RevocationPolicy::LeaverTerms(_), TerminationReason::Retirement) => {
+                Self::accelerate_to(&env, vault_id, effective_time);
+                0
+            }
+            _ => Self::internal_revoke_at(&env, vault_id, effective_time),
+        };
+
+        if forfeited > 0 {
+            let mut admin_balance: i128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::AdminBalance)
+                .unwrap_or(0);
+            admin_balance += forfeited;
+            env.storage()
+                .instance()
+                .set(&DataKey::AdminBalance, &admin_balance);
+        }
+
+        VaultTerminated {
+            vault_id,
+            reason,
+            effective_time,
+            forfeited,
+        }
+        .publish(&env);
+
+        forfeited
     }
 
-    // Revoke a specific amount of tokens from a vault and return them to admin
-    pub fn revoke_partial(env: Env, vault_id: u64, amount: i128) -> i128 {
+    // Admin-only: immediately vest `bps` of the vault's remaining unvested tokens
+    pub fn accelerate(env: Env, vault_id: u64, bps: u32) -> i128 {
         Self::require_admin(&env);
 
-        let returned = Self::internal_revoke_partial(&env, vault_id, amount);
+        Self::internal_accelerate(&env, vault_id, bps)
+    }
 
-        // Single admin balance update for this call
-        let mut admin_balance: i128 = env
+    // Internal helper: record accelerated tokens and emit event
+    fn internal_accelerate(env: &Env, vault_id: u64, bps: u32) -> i128 {
+        if bps == 0 || bps > BPS_DENOMINATOR {
+            panic!("Acceleration must be between 1 and 10000 bps");
+        }
+
+        let vault: Vault = env
             .storage()
             .instance()
-            .get(&DataKey::AdminBalance)
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if vault.is_irrevocable {
+            panic!("Vault is irrevocable");
+        }
+
+        let now = env.ledger().timestamp();
+        let unvested = vault.total_amount - Self::vested_amount(env, vault_id, &vault, now);
+        let amount = unvested * bps as i128 / BPS_DENOMINATOR as i128;
+
+        let accelerated: i128 = env
+            .storage()
+            .instance()
+            .get(&AccelerationDataKey::Accelerated(vault_id))
             .unwrap_or(0);
-        admin_balance += returned;
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+            .set(&AccelerationDataKey::Accelerated(vault_id), &(accelerated + amount));
 
-        returned
+        VestingAccelerated {
+            vault_id,
+            bps,
+            amount,
+        }
+        .publish(env);
+
+        amount
     }
 
-    // Internal helper: revoke a specific amount from a vault and emit event.
-    // Does NOT update admin balance — caller is responsible for a single aggregated transfer.
-    fn internal_revoke_partial(env: &Env, vault_id: u64, amount: i128) -> i128 {
-        let mut vault: Vault = env
+    // Total tokens vested ahead of schedule for a vault
+    pub fn get_accelerated_amount(env: Env, vault_id: u64) -> i128 {
+        env.storage()
+            .instance()
+            .get(&AccelerationDataKey::Accelerated(vault_id))
+            .unwrap_or(0)
+    }
+
+    // Admin-only: configure what a change of control does for a vault.
+    // Single-trigger rules apply as soon as the event is recorded (see
+    // trigger_acceleration); double-trigger rules also need a termination.
+    pub fn set_acceleration_rule(env: Env, vault_id: u64, bps: u32, double_trigger: bool, window: u64) {
+        Self::require_admin(&env);
+
+        let vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
@@ -1009,102 +1993,1544 @@ impl VestingContract {
         if vault.is_irrevocable {
             panic!("Vault is irrevocable");
         }
+        if bps == 0 || bps > BPS_DENOMINATOR {
+            panic!("Acceleration must be between 1 and 10000 bps");
+        }
 
-        let unvested_balance = vault.total_amount - vault.released_amount;
-        if amount <= 0 {
-            panic!("Amount to revoke must be positive");
+        let rule = AccelerationRule {
+            bps,
+            double_trigger,
+            window,
+            applied: false,
+        };
+        env.storage()
+            .instance()
+            .set(&AccelerationDataKey::Rule(vault_id), &rule);
+
+        AccelerationRuleSet { vault_id, rule }.publish(&env);
+    }
+
+    pub fn get_acceleration_rule(env: Env, vault_id: u64) -> Option<AccelerationRule> {
+        env.storage()
+            .instance()
+            .get(&AccelerationDataKey::Rule(vault_id))
+    }
+
+    // Admin-only: record the contract-level acceleration event (e.g. change of control)
+    pub fn record_change_of_control(env: Env) {
+        Self::require_admin(&env);
+
+        if env.storage().instance().has(&AccelerationDataKey::ChangeOfControl) {
+            panic!("Change of control already recorded");
         }
-        if amount > unvested_balance {
-            panic!("Amount exceeds unvested balance");
+
+        let timestamp = env.ledger().timestamp();
+        env.storage()
+            .instance()
+            .set(&AccelerationDataKey::ChangeOfControl, &timestamp);
+
+        ChangeOfControl { timestamp }.publish(&env);
+    }
+
+    pub fn get_change_of_control(env: Env) -> Option<u64> {
+        env.storage()
+            .instance()
+            .get(&AccelerationDataKey::ChangeOfControl)
+    }
+
+    // Permissionless: apply a vault's single-trigger rule once the event is recorded
+    pub fn trigger_acceleration(env: Env, vault_id: u64) -> i128 {
+        if !env.storage().instance().has(&AccelerationDataKey::ChangeOfControl) {
+            panic!("No change of control recorded");
         }
 
-        vault.released_amount += amount;
-        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        let rule: AccelerationRule = env
+            .storage()
+            .instance()
+            .get(&AccelerationDataKey::Rule(vault_id))
+            .unwrap_or_else(|| panic!("No acceleration rule for vault"));
 
-        let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(env, "TokensRevoked"), vault_id),
-            (amount, timestamp),
-        );
+        if rule.double_trigger {
+            panic!("Double-trigger rules apply on termination");
+        }
+        if rule.applied {
+            panic!("Acceleration already applied");
+        }
+
+        Self::apply_acceleration_rule(&env, vault_id, rule)
+    }
+
+    // Internal helper: accelerate per rule and mark it applied
+    fn apply_acceleration_rule(env: &Env, vault_id: u64, mut rule: AccelerationRule) -> i128 {
+        let amount = Self::internal_accelerate(env, vault_id, rule.bps);
+        rule.applied = true;
+        env.storage()
+            .instance()
+            .set(&AccelerationDataKey::Rule(vault_id), &rule);
 
         amount
     }
 
-    // Admin-only: Revoke many vaults in a single call and credit the admin once.
-    pub fn batch_revoke(env: Env, vault_ids: Vec<u64>) -> i128 {
+    // Admin-only: add tokens from the admin balance to an existing vault,
+    // optionally pushing its end date out. Returns the new total.
+    // The top-up joins the existing schedule rather than starting its own:
+    // vesting stays proportional to elapsed time, so the share of it that
+    // falls before now vests at once unless new_end_time pushes the end out.
+    pub fn top_up_vault(env: Env, vault_id: u64, amount: i128, new_end_time: Option<u64>) -> i128 {
         Self::require_admin(&env);
 
-        let mut total_returned: i128 = 0;
-        for id in vault_ids.iter() {
-            let returned = Self::internal_revoke_full(&env, id);
-            total_returned += returned;
+        if amount <= 0 {
+            panic!("Top-up amount must be positive");
         }
 
-        // Single admin balance update for the whole batch
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        let previous = vault.clone();
+
+        vault.total_amount = vault
+            .total_amount
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, VestingError::AmountTooLarge));
+        if let Some(end_time) = new_end_time {
+            // Shortening the schedule is an amendment and needs the beneficiary's consent
+            if end_time < vault.end_time {
+                panic!("New end time must not be earlier than the current one");
+            }
+            vault.end_time = end_time;
+        }
+        // The grown vault must still pass the creation checks
+        Self::require_valid_vault(
+            &env,
+            vault.total_amount,
+            vault.start_time,
+            vault.end_time,
+            vault.keeper_fee,
+            vault.step_duration,
+        );
+
         let mut admin_balance: i128 = env
             .storage()
             .instance()
             .get(&DataKey::AdminBalance)
             .unwrap_or(0);
-        admin_balance += total_returned;
+        if admin_balance < amount {
+            panic!("Insufficient admin balance");
+        }
+        admin_balance -= amount;
         env.storage()
             .instance()
             .set(&DataKey::AdminBalance, &admin_balance);
 
-        total_returned
+        Self::record_schedule_version(&env, vault_id, &previous);
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        Self::adjust_locked_balance(&env, &vault.owner, amount);
+
+        VaultToppedUp {
+            vault_id,
+            amount,
+            total_amount: vault.total_amount,
+            end_time: vault.end_time,
+        }
+        .publish(&env);
+
+        vault.total_amount
     }
 
-    // Clawback a vault within the grace period (1 hour)
-    pub fn clawback_vault(env: Env, vault_id: u64) -> i128 {
+    // Admin-only: propose a new schedule for a vault. It only takes effect
+    // once the beneficiary accepts it with amend_schedule.
+    pub fn propose_amendment(env: Env, vault_id: u64, start_time: u64, end_time: u64, step_duration: u64) {
+        Self::require_admin(&env);
+
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        Self::require_valid_vault(&env, vault.total_amount, start_time, end_time, vault.keeper_fee, step_duration);
+
+        let amendment = ScheduleAmendment {
+            start_time,
+            end_time,
+            step_duration,
+            proposed_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .instance()
+            .set(&AmendmentDataKey::Pending(vault_id), &amendment);
+
+        AmendmentProposed {
+            vault_id,
+            amendment,
+        }
+        .publish(&env);
+    }
+
+    // Admin-only: withdraw a proposed amendment
+    pub fn cancel_amendment(env: Env, vault_id: u64) {
         Self::require_admin(&env);
 
+        if !env.storage().instance().has(&AmendmentDataKey::Pending(vault_id)) {
+            panic!("No pending amendment");
+        }
+        env.storage()
+            .instance()
+            .remove(&AmendmentDataKey::Pending(vault_id));
+
+        AmendmentCancelled { vault_id }.publish(&env);
+    }
+
+    pub fn get_pending_amendment(env: Env, vault_id: u64) -> Option<ScheduleAmendment> {
+        env.storage()
+            .instance()
+            .get(&AmendmentDataKey::Pending(vault_id))
+    }
+
+    // Beneficiary consents to the pending amendment, replacing the schedule.
+    // The previous schedule is kept in the vault's schedule history.
+    pub fn amend_schedule(env: Env, vault_id: u64) {
         let mut vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
-            .unwrap_or_else(|| {
-                panic!("Vault not found");
-            });
+            .unwrap_or_else(|| panic!("Vault not found"));
 
-        let now = env.ledger().timestamp();
-        let grace_period = 3600; // 1 hour in seconds
+        vault.owner.require_auth();
 
-        if now > vault.creation_time + grace_period {
-            panic!("Grace period expired");
+        let amendment: ScheduleAmendment = env
+            .storage()
+            .instance()
+            .get(&AmendmentDataKey::Pending(vault_id))
+            .unwrap_or_else(|| panic!("No pending amendment"));
+
+        if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
+            panic!("Milestone vaults cannot be amended");
+        }
+        if vault.suspended_at.is_some() {
+            panic!("Resume vesting before amending");
         }
 
-        if vault.released_amount > 0 {
-            panic!("Tokens already claimed");
+        Self::record_schedule_version(&env, vault_id, &vault);
+
+        // The new schedule is absolute: earlier suspensions were already
+        // reflected in the end date it replaces
+        vault.start_time = amendment.start_time;
+        vault.end_time = amendment.end_time;
+        vault.step_duration = amendment.step_duration;
+        vault.suspended_duration = 0;
+
+        let now = env.ledger().timestamp();
+        if Self::vested_amount(&env, vault_id, &vault, now) < vault.released_amount {
+            panic!("Amendment would vest less than already claimed");
         }
 
-        // Refund admin
-        let mut admin_balance: i128 = env
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        env.storage()
+            .instance()
+            .remove(&AmendmentDataKey::Pending(vault_id));
+
+        ScheduleAmended {
+            vault_id,
+            start_time: amendment.start_time,
+            end_time: amendment.end_time,
+            step_duration: amendment.step_duration,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_schedule_history(env: Env, vault_id: u64) -> Vec<ScheduleVersion> {
+        env.storage()
+            .instance()
+            .get(&AmendmentDataKey::ScheduleHistory(vault_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Internal helper: append the vault's current schedule to its history
+    fn record_schedule_version(env: &Env, vault_id: u64, vault: &Vault) {
+        let mut history: Vec<ScheduleVersion> = env
             .storage()
             .instance()
-            .get(&DataKey::AdminBalance)
-            .unwrap_or(0);
-        admin_balance += vault.total_amount;
+            .get(&AmendmentDataKey::ScheduleHistory(vault_id))
+            .unwrap_or(Vec::new(env));
+        history.push_back(ScheduleVersion {
+            total_amount: vault.total_amount,
+            start_time: vault.start_time,
+            end_time: vault.end_time,
+            step_duration: vault.step_duration,
+            replaced_at: env.ledger().timestamp(),
+        });
         env.storage()
             .instance()
-            .set(&DataKey::AdminBalance, &admin_balance);
+            .set(&AmendmentDataKey::ScheduleHistory(vault_id), &history);
+    }
 
-        // Mark as released/revoked so it can't be claimed
-        vault.released_amount = vault.total_amount;
+    // Admin-only: pause vesting (e.g. unpaid leave) until resume_vesting
+    pub fn suspend_vesting(env: Env, vault_id: u64) {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if vault.is_irrevocable {
+            panic!("Vault is irrevocable");
+        }
+        if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
+            panic!("Suspension not supported for milestone vaults");
+        }
+        if vault.suspended_at.is_some() {
+            panic!("Vesting already suspended");
+        }
+
+        let timestamp = env.ledger().timestamp();
+        if Self::vested_amount(&env, vault_id, &vault, timestamp) >= vault.total_amount {
+            panic!("Vault is fully vested");
+        }
+        vault.suspended_at = Some(timestamp);
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+
+        let mut history: Vec<SuspensionPeriod> = env
+            .storage()
+            .instance()
+            .get(&SuspensionDataKey::History(vault_id))
+            .unwrap_or(Vec::new(&env));
+        history.push_back(SuspensionPeriod {
+            suspended_at: timestamp,
+            resumed_at: None,
+        });
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .set(&SuspensionDataKey::History(vault_id), &history);
 
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "VaultClawedBack"), vault_id),
-            vault.total_amount,
+        VestingSuspended {
+            vault_id,
+            timestamp,
+        }
+        .publish(&env);
+    }
+
+    // Admin-only: resume a suspended vault, extending its end date by the
+    // time spent suspended. Returns the extension in seconds.
+    pub fn resume_vesting(env: Env, vault_id: u64) -> u64 {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if vault.suspended_at.is_none() {
+            panic!("Vesting is not suspended");
+        }
+        let extension = Self::end_suspension(&env, vault_id, &mut vault);
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+
+        extension
+    }
+
+    // Internal helper: close the vault's open suspension now, extending its end
+    // date by the time spent suspended. The caller saves the vault.
+    fn end_suspension(env: &Env, vault_id: u64, vault: &mut Vault) -> u64 {
+        let suspended_at = match vault.suspended_at {
+            Some(suspended_at) => suspended_at,
+            None => return 0,
+        };
+
+        // Only time after start_time delays vesting
+        let timestamp = env.ledger().timestamp();
+        let from = if suspended_at > vault.start_time { suspended_at } else { vault.start_time };
+        let extension = timestamp.saturating_sub(from);
+
+        vault.suspended_at = None;
+        vault.suspended_duration += extension;
+        vault.end_time += extension;
+
+        let mut history: Vec<SuspensionPeriod> = env
+            .storage()
+            .instance()
+            .get(&SuspensionDataKey::History(vault_id))
+            .unwrap_or(Vec::new(env));
+        let last = history.len() - 1;
+        let mut period = history.get(last).unwrap();
+        period.resumed_at = Some(timestamp);
+        history.set(last, period);
+        env.storage()
+            .instance()
+            .set(&SuspensionDataKey::History(vault_id), &history);
+
+        VestingResumed {
+            vault_id,
+            timestamp,
+            extension,
+        }
+        .publish(env);
+
+        extension
+    }
+
+    pub fn get_suspension_history(env: Env, vault_id: u64) -> Vec<SuspensionPeriod> {
+        env.storage()
+            .instance()
+            .get(&SuspensionDataKey::History(vault_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Internal helper: make the whole vault vest by `at` (if it would not already)
+    fn accelerate_to(env: &Env, vault_id: u64, at: u64) {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        // Nothing is left to pause once the vault vests in full; close the
+        // suspension as resume_vesting would, so the pause stays on record
+        Self::end_suspension(env, vault_id, &mut vault);
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::VaultMilestones(vault_id));
+        let end_time = if at > vault.start_time { at } else { vault.start_time };
+        if end_time < vault.end_time {
+            vault.end_time = end_time;
+            vault.step_duration = 0;
+        }
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        Self::publish_schedule_shortened(env, vault_id, &vault);
+    }
+
+    // Internal helper: revocation and termination reshape the schedule in ways
+    // their own events don't spell out, so publish the result for indexers
+    fn publish_schedule_shortened(env: &Env, vault_id: u64, vault: &Vault) {
+        ScheduleShortened {
+            vault_id,
+            total_amount: vault.total_amount,
+            end_time: vault.end_time,
+            step_duration: vault.step_duration,
+            suspended_at: vault.suspended_at,
+        }
+        .publish(env);
+    }
+
+    // Internal helper: revoked tokens must not be staked or unbonding
+    fn require_revocable_liquidity(env: &Env, vault_id: u64, vault: &Vault, amount: i128) {
+        let unbonding = Self::settle_unbonding(env, vault_id);
+        let liquid = vault.total_amount - vault.released_amount - vault.staked_amount - unbonding;
+        if amount > liquid {
+            panic!("Unstake tokens before revoking");
+        }
+    }
+
+    // Admin-only: Revoke tokens from a vault and return them to admin
+    pub fn revoke_tokens(env: Env, vault_id: u64) -> i128 {
+        Self::require_admin(&env);
+
+        let returned = Self::internal_revoke_full(&env, vault_id);
+
+        // Single admin balance update for this call
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        admin_balance += returned;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        returned
+    }
+
+    // Revoke a specific amount of tokens from a vault and return them to admin
+    pub fn revoke_partial(env: Env, vault_id: u64, amount: i128) -> i128 {
+        Self::require_admin(&env);
+
+        let returned = Self::internal_revoke_partial(&env, vault_id, amount);
+
+        // Single admin balance update for this call
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        admin_balance += returned;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        returned
+    }
+
+    // Internal helper: revoke a specific amount from a vault and emit event.
+    // Does NOT update admin balance — caller is responsible for a single aggregated transfer.
+    fn internal_revoke_partial(env: &Env, vault_id: u64, amount: i128) -> i128 {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if vault.is_irrevocable {
+            panic!("Vault is irrevocable");
+        }
+
+        if env.storage().instance().has(&DataKey::VaultMilestones(vault_id)) {
+            panic!("Partial revocation not supported for milestone vaults");
+        }
+
+        let timestamp = env.ledger().timestamp();
+        let mut vested = Self::vested_amount(env, vault_id, &vault, timestamp);
+        if vested < vault.released_amount {
+            vested = vault.released_amount;
+        }
+        let unvested_balance = vault.total_amount - vested;
+        if amount <= 0 {
+            panic!("Amount to revoke must be positive");
+        }
+        if amount > unvested_balance {
+            panic!("Amount exceeds unvested balance");
+        }
+        Self::require_revocable_liquidity(env, vault_id, &vault, amount);
+
+        // Cut `amount` off the tail of the schedule. Shrinking the duration in
+        // proportion keeps the vesting rate, so already-vested tokens are untouched.
+        let duration = (vault.end_time - vault.start_time - vault.suspended_duration) as i128;
+        let new_total = vault.total_amount - amount;
+        vault.end_time = vault.start_time
+            + vault.suspended_duration
+            + (duration * new_total / vault.total_amount) as u64;
+        vault.total_amount = new_total;
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        Self::adjust_locked_balance(env, &vault.owner, -amount);
+        Self::publish_schedule_shortened(env, vault_id, &vault);
+
+        TokensRevoked {
+            vault_id,
+            vested_amount: vested,
+            unvested_amount: amount,
+            beneficiary: vault.owner.clone(),
+            timestamp,
+        }
+        .publish(env);
+
+        amount
+    }
+
+    // Admin-only: Revoke many vaults in a single call and credit the admin once.
+    pub fn batch_revoke(env: Env, vault_ids: Vec<u64>) -> i128 {
+        Self::require_admin(&env);
+
+        let mut total_returned: i128 = 0;
+        for id in vault_ids.iter() {
+            let returned = Self::internal_revoke_full(&env, id);
+            total_returned += returned;
+        }
+
+        // Single admin balance update for the whole batch
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        admin_balance += total_returned;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        total_returned
+    }
+
+    // Clawback a vault within the grace period (1 hour)
+    pub fn clawback_vault(env: Env, vault_id: u64) -> i128 {
+        Self::require_admin(&env);
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        let now = env.ledger().timestamp();
+        let grace_period = 3600; // 1 hour in seconds
+
+        if now > vault.creation_time + grace_period {
+            panic!("Grace period expired");
+        }
+
+        if vault.released_amount > 0 {
+            panic!("Tokens already claimed");
+        }
+
+        // Refund admin
+        let mut admin_balance: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminBalance)
+            .unwrap_or(0);
+        admin_balance += vault.total_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminBalance, &admin_balance);
+
+        // Mark as released/revoked so it can't be claimed
+        vault.released_amount = vault.total_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_id), &vault);
+        Self::adjust_locked_balance(&env, &vault.owner, -vault.total_amount);
+
+        // Emit event
+        VaultClawedBack {
+            vault_id,
+            amount: vault.total_amount,
+        }
+        .publish(&env);
+
+        vault.total_amount
+    }
+
+    // Beneficiary-only: move `amount` of a transferable vault into a new vault
+    // on the same schedule. Claimed and accelerated tokens are split in
+    // proportion, so both vaults vest exactly as the original would. The child
+    // stays with the owner; a different `new_owner` gets a pending transfer to
+    // accept, under the same policy as transfer_vault.
+    pub fn split_vault(env: Env, vault_id: u64, amount: i128, new_owner: Address) -> u64 {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.owner.require_auth();
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+        if amount <= 0 || amount >= vault.total_amount {
+            panic!("Split amount must be between zero and the vault total");
+        }
+        if vault.staked_amount > 0 || Self::settle_unbonding(&env, vault_id) > 0 {
+            panic!("Unstake tokens before splitting");
+        }
+        if env
+            .storage()
+            .instance()
+            .has(&PayoutDataKey::VaultSplits(vault_id))
+        {
+            // Payout splits route the owner's claims; set them per vault
+            panic!("Clear payout splits before splitting");
+        }
+
+        let child_released = vault.released_amount * amount / vault.total_amount;
+        let accelerated: i128 = env
+            .storage()
+            .instance()
+            .get(&AccelerationDataKey::Accelerated(vault_id))
+            .unwrap_or(0);
+        let child_accelerated = accelerated * amount / vault.total_amount;
+
+        let mut vault_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultCount)
+            .unwrap_or(0);
+        vault_count += 1;
+
+        // The child keeps the parent's creation time, so the clawback grace
+        // period and transfer lock-up still count from the original grant
+        let child = Vault {
+            delegate: None,
+            total_amount: amount,
+            released_amount: child_released,
+            ..vault.clone()
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_count), &child);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCount, &vault_count);
+
+        vault.total_amount -= amount;
+        vault.released_amount -= child_released;
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_id), &vault);
+
+        if accelerated > 0 {
+            env.storage()
+                .instance()
+                .set(&AccelerationDataKey::Accelerated(vault_id), &(accelerated - child_accelerated));
+            env.storage()
+                .instance()
+                .set(&AccelerationDataKey::Accelerated(vault_count), &child_accelerated);
+        }
+        if let Some(milestones) = env
+            .storage()
+            .instance()
+            .get::<_, Vec<Milestone>>(&DataKey::VaultMilestones(vault_id))
+        {
+            env.storage()
+                .instance()
+                .set(&DataKey::VaultMilestones(vault_count), &milestones);
+        }
+        if let Some(rule) = env
+            .storage()
+            .instance()
+            .get::<_, AccelerationRule>(&AccelerationDataKey::Rule(vault_id))
+        {
+            env.storage()
+                .instance()
+                .set(&AccelerationDataKey::Rule(vault_count), &rule);
+        }
+        // The child inherits suspended_at and suspended_duration, so it needs
+        // the periods behind them, including an open one to resume later
+        if let Some(history) = env
+            .storage()
+            .instance()
+            .get::<_, Vec<SuspensionPeriod>>(&SuspensionDataKey::History(vault_id))
+        {
+            env.storage()
+                .instance()
+                .set(&SuspensionDataKey::History(vault_count), &history);
+        }
+        if let Some(adapter) = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&StakingDataKey::VaultAdapter(vault_id))
+        {
+            env.storage()
+                .instance()
+                .set(&StakingDataKey::VaultAdapter(vault_count), &adapter);
+        }
+
+        let mut user_vaults: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserVaults(vault.owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        user_vaults.push_back(vault_count);
+        env.storage()
+            .instance()
+            .set(&DataKey::UserVaults(vault.owner.clone()), &user_vaults);
+
+        VaultSplit {
+            vault_id,
+            child_vault_id: vault_count,
+            new_owner: new_owner.clone(),
+            amount,
+        }
+        .publish(&env);
+
+        if new_owner != vault.owner {
+            Self::request_transfer(&env, vault_count, &child, &new_owner);
+        }
+
+        vault_count
+    }
+
+    // Beneficiary-only: fold vault `other` into `vault_id`. Both must belong to
+    // the same owner and follow an identical schedule; `other` is removed.
+    pub fn merge_vaults(env: Env, vault_id: u64, other: u64) {
+        if vault_id == other {
+            panic!("Cannot merge a vault with itself");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+        let merged: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(other))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.owner.require_auth();
+
+        if merged.owner != vault.owner {
+            panic!("Vaults have different owners");
+        }
+        if !vault.is_initialized || !merged.is_initialized {
+            panic!("Vault not initialized");
+        }
+        // creation_time too: the clawback grace period and transfer lock-up count from it
+        let same_schedule = vault.creation_time == merged.creation_time
+            && vault.start_time == merged.start_time
+            && vault.end_time == merged.end_time
+            && vault.step_duration == merged.step_duration
+            && vault.suspended_at == merged.suspended_at
+            && vault.suspended_duration == merged.suspended_duration
+            && vault.is_irrevocable == merged.is_irrevocable
+            && vault.is_transferable == merged.is_transferable
+            && vault.revocation_policy == merged.revocation_policy;
+        if !same_schedule
+            || env.storage().instance().has(&DataKey::VaultMilestones(vault_id))
+            || env.storage().instance().has(&DataKey::VaultMilestones(other))
+        {
+            panic!("Vaults must have identical schedules");
+        }
+        if merged.staked_amount > 0 || Self::settle_unbonding(&env, other) > 0 {
+            panic!("Unstake tokens before merging");
+        }
+
+        vault.total_amount += merged.total_amount;
+        vault.released_amount += merged.released_amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_id), &vault);
+
+        let merged_accelerated: i128 = env
+            .storage()
+            .instance()
+            .get(&AccelerationDataKey::Accelerated(other))
+            .unwrap_or(0);
+        if merged_accelerated > 0 {
+            let accelerated: i128 = env
+                .storage()
+                .instance()
+                .get(&AccelerationDataKey::Accelerated(vault_id))
+                .unwrap_or(0);
+            env.storage()
+                .instance()
+                .set(&AccelerationDataKey::Accelerated(vault_id), &(accelerated + merged_accelerated));
+        }
+
+        // Drop everything keyed by the merged vault
+        env.storage().instance().remove(&DataKey::VaultData(other));
+        env.storage().instance().remove(&DelegateDataKey::VaultDelegates(other));
+        env.storage().instance().remove(&PayoutDataKey::VaultSplits(other));
+        env.storage().instance().remove(&StakingDataKey::VaultAdapter(other));
+        env.storage().instance().remove(&StakingDataKey::VaultValidators(other));
+        env.storage().instance().remove(&StakingDataKey::VaultUnbonding(other));
+        env.storage().instance().remove(&AccelerationDataKey::Accelerated(other));
+        env.storage().instance().remove(&AccelerationDataKey::Rule(other));
+        env.storage().instance().remove(&AmendmentDataKey::Pending(other));
+        env.storage().instance().remove(&AmendmentDataKey::ScheduleHistory(other));
+        env.storage().instance().remove(&SuspensionDataKey::History(other));
+        env.storage().instance().remove(&TransferDataKey::Pending(other));
+        env.storage().instance().remove(&NftDataKey::Approved(other));
+        env.storage().instance().remove(&RecoveryDataKey::Guardians(other));
+        env.storage().instance().remove(&RecoveryDataKey::Pending(other));
+
+        let user_vaults: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserVaults(vault.owner.clone()))
+            .unwrap_or(Vec::new(&env));
+        let mut remaining = Vec::new(&env);
+        for id in user_vaults.iter() {
+            if id != other {
+                remaining.push_back(id);
+            }
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::UserVaults(vault.owner.clone()), &remaining);
+
+        VaultsMerged {
+            vault_id,
+            merged_vault_id: other,
+            merged_amount: merged.total_amount,
+        }
+        .publish(&env);
+    }
+
+    // Transfer vault ownership to another beneficiary (if transferable)
+    pub fn transfer_vault(env: Env, vault_id: u64, new_beneficiary: Address) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+
+        // Check if caller is the vault owner
+        let caller = env.current_contract_address();
+        if caller != vault.owner {
+            panic!("Only vault owner can transfer");
+        }
+
+        // The recipient has to accept before ownership moves
+        Self::request_transfer(&env, vault_id, &vault, &new_beneficiary);
+    }
+
+    // Admin-only: set the policy every vault transfer must satisfy
+    pub fn set_transfer_policy(env: Env, policy: TransferPolicy) {
+        Self::require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&TransferDataKey::Policy, &policy);
+
+        TransferPolicySet { policy }.publish(&env);
+    }
+
+    pub fn get_transfer_policy(env: Env) -> TransferPolicy {
+        env.storage()
+            .instance()
+            .get(&TransferDataKey::Policy)
+            .unwrap_or(TransferPolicy {
+                requires_admin_approval: false,
+                allow_list_only: false,
+                lockup_period: 0,
+            })
+    }
+
+    // Admin-only: allow `recipient` to receive vaults under an allow-list policy
+    pub fn add_transfer_recipient(env: Env, recipient: Address) {
+        Self::require_admin(&env);
+
+        let mut allow_list = Self::get_transfer_allow_list(env.clone());
+        if !allow_list.contains(&recipient) {
+            allow_list.push_back(recipient.clone());
+            env.storage()
+                .instance()
+                .set(&TransferDataKey::AllowList, &allow_list);
+
+            TransferRecipientAdded { recipient }.publish(&env);
+        }
+    }
+
+    // Admin-only: remove `recipient` from the transfer allow-list
+    pub fn remove_transfer_recipient(env: Env, recipient: Address) {
+        Self::require_admin(&env);
+
+        let mut allow_list = Self::get_transfer_allow_list(env.clone());
+        if let Some(index) = allow_list.first_index_of(&recipient) {
+            allow_list.remove(index);
+            env.storage()
+                .instance()
+                .set(&TransferDataKey::AllowList, &allow_list);
+
+            TransferRecipientRemoved { recipient }.publish(&env);
+        }
+    }
+
+    pub fn get_transfer_allow_list(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&TransferDataKey::AllowList)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Admin-only: approve a pending transfer when the policy requires it
+    pub fn approve_vault_transfer(env: Env, vault_id: u64) {
+        Self::require_admin(&env);
+
+        let mut pending = Self::pending_transfer(&env, vault_id);
+        pending.admin_approved = true;
+        env.storage()
+            .instance()
+            .set(&TransferDataKey::Pending(vault_id), &pending);
+
+        TransferApproved {
+            vault_id,
+            to: pending.to,
+        }
+        .publish(&env);
+    }
+
+    // Recipient accepts a pending transfer and becomes the vault owner
+    pub fn accept_vault_transfer(env: Env, vault_id: u64) {
+        let pending = Self::pending_transfer(&env, vault_id);
+        pending.to.require_auth();
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        // Ownership may have moved by other means since the request
+        if vault.owner != pending.from {
+            panic!("Pending transfer is stale");
+        }
+        // The policy may have changed since the request
+        let policy = Self::require_transfer_allowed(&env, &vault, &pending.to);
+        if policy.requires_admin_approval && !pending.admin_approved {
+            panic!("Transfer requires admin approval");
+        }
+
+        Self::reassign_owner(&env, vault_id, &mut vault, &pending.to);
+
+        VaultTransferred {
+            vault_id,
+            from: pending.from.clone(),
+            to: pending.to.clone(),
+            kind: TransferKind::Owner,
+        }
+        .publish(&env);
+        NftTransfer {
+            from: pending.from,
+            to: pending.to,
+            token_id: vault_id,
+        }
+        .publish(&env);
+    }
+
+    // Owner-only: withdraw a pending transfer
+    pub fn cancel_vault_transfer(env: Env, vault_id: u64) {
+        let pending = Self::pending_transfer(&env, vault_id);
+        pending.from.require_auth();
+
+        env.storage()
+            .instance()
+            .remove(&TransferDataKey::Pending(vault_id));
+
+        TransferCancelled {
+            vault_id,
+            to: pending.to,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_pending_transfer(env: Env, vault_id: u64) -> Option<PendingTransfer> {
+        env.storage()
+            .instance()
+            .get(&TransferDataKey::Pending(vault_id))
+    }
+
+    fn pending_transfer(env: &Env, vault_id: u64) -> PendingTransfer {
+        env.storage()
+            .instance()
+            .get(&TransferDataKey::Pending(vault_id))
+            .unwrap_or_else(|| panic!("No pending transfer"))
+    }
+
+    // Internal helper: record a transfer of `vault` to `to` for the recipient
+    // to accept. Replaces any earlier pending transfer of the vault.
+    fn request_transfer(env: &Env, vault_id: u64, vault: &Vault, to: &Address) {
+        if *to == vault.owner {
+            panic!("Vault already belongs to recipient");
+        }
+        Self::require_transfer_allowed(env, vault, to);
+
+        let pending = PendingTransfer {
+            from: vault.owner.clone(),
+            to: to.clone(),
+            admin_approved: false,
+            requested_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .instance()
+            .set(&TransferDataKey::Pending(vault_id), &pending);
+
+        TransferRequested {
+            vault_id,
+            from: vault.owner.clone(),
+            to: to.clone(),
+        }
+        .publish(env);
+    }
+
+    // Internal helper: lock-up and allow-list checks for moving `vault` to `to`
+    fn require_transfer_allowed(env: &Env, vault: &Vault, to: &Address) -> TransferPolicy {
+        let policy = Self::get_transfer_policy(env.clone());
+
+        if env.ledger().timestamp() < vault.creation_time + policy.lockup_period {
+            panic!("Vault is still in its transfer lock-up period");
+        }
+        if policy.allow_list_only && !Self::get_transfer_allow_list(env.clone()).contains(to) {
+            panic!("Recipient is not on the transfer allow-list");
+        }
+
+        policy
+    }
+
+    // Internal helper: hand a vault to `new_owner`, moving it between the
+    // owners' UserVaults (or pending index, for a lazy vault) and voting power
+    // and dropping per-owner state (delegates, NFT approval, pending transfer,
+    // guardians, recovery). Saves the vault.
+    fn reassign_owner(env: &Env, vault_id: u64, vault: &mut Vault, new_owner: &Address) {
+        let old_owner = vault.owner.clone();
+
+        if !vault.is_initialized {
+            Self::remove_pending_vault(env, &old_owner, vault_id);
+            Self::push_pending_vault(env, new_owner, vault_id);
+        } else {
+            // Update UserVaults
+            // Remove from old owner
+            let old_user_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(old_owner.clone()))
+                .unwrap_or(Vec::new(env));
+
+            let mut new_old_user_vaults = Vec::new(env);
+            for id in old_user_vaults.iter() {
+                if id != vault_id {
+                    new_old_user_vaults.push_back(id);
+                }
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(old_owner.clone()), &new_old_user_vaults);
+
+            // Add to new owner
+            let mut new_user_vaults: Vec<u64> = env
+                .storage()
+                .instance()
+                .get(&DataKey::UserVaults(new_owner.clone()))
+                .unwrap_or(Vec::new(env));
+            new_user_vaults.push_back(vault_id);
+            env.storage()
+                .instance()
+                .set(&DataKey::UserVaults(new_owner.clone()), &new_user_vaults);
+        }
+
+        // Update vault
+        vault.owner = new_owner.clone();
+        vault.delegate = None; // Reset delegate on transfer
+        env.storage()
+            .instance()
+            .remove(&DelegateDataKey::VaultDelegates(vault_id));
+        env.storage()
+            .instance()
+            .remove(&NftDataKey::Approved(vault_id));
+        env.storage()
+            .instance()
+            .remove(&TransferDataKey::Pending(vault_id));
+        env.storage()
+            .instance()
+            .remove(&RecoveryDataKey::Guardians(vault_id));
+        env.storage()
+            .instance()
+            .remove(&RecoveryDataKey::Pending(vault_id));
+        
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultData(vault_id), &*vault);
+        Self::move_locked_balance(
+            env,
+            &old_owner,
+            new_owner,
+            vault.total_amount - vault.released_amount,
         );
+    }
+
+    // Owner-only: register guardians who can jointly recover the vault if the
+    // owner's key is lost. An empty list removes the guardians.
+    pub fn set_guardians(env: Env, vault_id: u64, guardians: Vec<Address>, threshold: u32) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        vault.owner.require_auth();
+
+        // A changed guardian set invalidates any recovery in progress
+        env.storage()
+            .instance()
+            .remove(&RecoveryDataKey::Pending(vault_id));
+
+        if guardians.is_empty() {
+            env.storage()
+                .instance()
+                .remove(&RecoveryDataKey::Guardians(vault_id));
+        } else {
+            if guardians.len() > MAX_GUARDIANS {
+                panic!("Too many guardians");
+            }
+            if threshold == 0 || threshold > guardians.len() {
+                panic!("Threshold must be between 1 and the number of guardians");
+            }
+            for (i, guardian) in guardians.iter().enumerate() {
+                if guardian == vault.owner {
+                    panic!("Owner cannot be a guardian");
+                }
+                if guardians.first_index_of(&guardian) != Some(i as u32) {
+                    panic!("Duplicate guardian");
+                }
+            }
+
+            let config = GuardianConfig {
+                guardians: guardians.clone(),
+                threshold,
+            };
+            env.storage()
+                .instance()
+                .set(&RecoveryDataKey::Guardians(vault_id), &config);
+        }
+
+        GuardiansSet {
+            vault_id,
+            guardians,
+            threshold,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_guardians(env: Env, vault_id: u64) -> Option<GuardianConfig> {
+        env.storage()
+            .instance()
+            .get(&RecoveryDataKey::Guardians(vault_id))
+    }
+
+    // Admin-only: set how long a recovery waits after reaching its threshold
+    pub fn set_recovery_delay(env: Env, delay: u64) {
+        Self::require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&RecoveryDataKey::Delay, &delay);
+
+        RecoveryDelaySet { delay }.publish(&env);
+    }
+
+    pub fn get_recovery_delay(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&RecoveryDataKey::Delay)
+            .unwrap_or(DEFAULT_RECOVERY_DELAY)
+    }
+
+    // Guardian-only: start recovering the vault to `new_owner`. Counts as the
+    // initiating guardian's approval.
+    pub fn initiate_recovery(env: Env, vault_id: u64, guardian: Address, new_owner: Address) {
+        guardian.require_auth();
+
+        let config = Self::require_guardian(&env, vault_id, &guardian);
+        if env.storage().instance().has(&RecoveryDataKey::Pending(vault_id)) {
+            panic!("Recovery already in progress");
+        }
+
+        let now = env.ledger().timestamp();
+        let request = RecoveryRequest {
+            new_owner: new_owner.clone(),
+            approvals: Vec::from_array(&env, [guardian.clone()]),
+            initiated_at: now,
+            threshold_reached_at: if config.threshold <= 1 { Some(now) } else { None },
+        };
+        env.storage()
+            .instance()
+            .set(&RecoveryDataKey::Pending(vault_id), &request);
+
+        RecoveryInitiated {
+            vault_id,
+            guardian,
+            new_owner,
+        }
+        .publish(&env);
+    }
+
+    // Guardian-only: approve the recovery in progress
+    pub fn approve_recovery(env: Env, vault_id: u64, guardian: Address) {
+        guardian.require_auth();
+
+        let config = Self::require_guardian(&env, vault_id, &guardian);
+        let mut request = Self::pending_recovery(&env, vault_id);
+        if request.approvals.contains(&guardian) {
+            panic!("Guardian already approved");
+        }
+
+        request.approvals.push_back(guardian.clone());
+        if request.threshold_reached_at.is_none() && request.approvals.len() >= config.threshold {
+            request.threshold_reached_at = Some(env.ledger().timestamp());
+        }
+        env.storage()
+            .instance()
+            .set(&RecoveryDataKey::Pending(vault_id), &request);
+
+        RecoveryApproved {
+            vault_id,
+            guardian,
+            approvals: request.approvals.len(),
+        }
+        .publish(&env);
+    }
+
+    // Permissionless: hand the vault to the recovery's new owner once enough
+    // guardians approved and the delay has passed without the owner cancelling
+    pub fn complete_recovery(env: Env, vault_id: u64) {
+        let request = Self::pending_recovery(&env, vault_id);
+        let ready_at = request
+            .threshold_reached_at
+            .unwrap_or_else(|| panic!("Not enough guardian approvals"));
+        if env.ledger().timestamp() < ready_at + Self::get_recovery_delay(env.clone()) {
+            panic!("Recovery delay has not passed");
+        }
+
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        // The recovered key belongs to the same beneficiary, so the guardians stay
+        let guardians: Option<GuardianConfig> = env
+            .storage()
+            .instance()
+            .get(&RecoveryDataKey::Guardians(vault_id));
+        let old_owner = vault.owner.clone();
+        Self::reassign_owner(&env, vault_id, &mut vault, &request.new_owner);
+        if let Some(config) = guardians {
+            env.storage()
+                .instance()
+                .set(&RecoveryDataKey::Guardians(vault_id), &config);
+        }
+
+        VaultTransferred {
+            vault_id,
+            from: old_owner,
+            to: request.new_owner,
+            kind: TransferKind::Recovery,
+        }
+        .publish(&env);
+    }
+
+    // Owner-only: stop a recovery the owner did not ask for
+    pub fn cancel_recovery(env: Env, vault_id: u64) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        vault.owner.require_auth();
+
+        let request = Self::pending_recovery(&env, vault_id);
+        env.storage()
+            .instance()
+            .remove(&RecoveryDataKey::Pending(vault_id));
+
+        RecoveryCancelled {
+            vault_id,
+            new_owner: request.new_owner,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_recovery(env: Env, vault_id: u64) -> Option<RecoveryRequest> {
+        env.storage()
+            .instance()
+            .get(&RecoveryDataKey::Pending(vault_id))
+    }
+
+    fn pending_recovery(env: &Env, vault_id: u64) -> RecoveryRequest {
+        env.storage()
+            .instance()
+            .get(&RecoveryDataKey::Pending(vault_id))
+            .unwrap_or_else(|| panic!("No recovery in progress"))
+    }
+
+    fn require_guardian(env: &Env, vault_id: u64, guardian: &Address) -> GuardianConfig {
+        let config: GuardianConfig = env
+            .storage()
+            .instance()
+            .get(&RecoveryDataKey::Guardians(vault_id))
+            .unwrap_or_else(|| panic!("Vault has no guardians"));
+        if !config.guardians.contains(guardian) {
+            panic!("Not a guardian of this vault");
+        }
+        config
+    }
+
+    // Rotate beneficiary key (security feature, allows self-transfer even if non-transferable)
+    pub fn rotate_beneficiary_key(env: Env, vault_id: u64, caller: Address, new_address: Address) {
+        let mut vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        // The current owner, or a delegate holding the Rotate scope
+        caller.require_auth();
+        if caller != vault.owner {
+            Self::require_delegate_scope(&env, vault_id, &caller, DelegateScope::Rotate);
+        }
+
+        // The new key belongs to the same beneficiary, so the guardians stay;
+        // everything else the old key granted is dropped
+        let guardians: Option<GuardianConfig> = env
+            .storage()
+            .instance()
+            .get(&RecoveryDataKey::Guardians(vault_id));
+        let old_owner = vault.owner.clone();
+        Self::reassign_owner(&env, vault_id, &mut vault, &new_address);
+        if let Some(config) = guardians {
+            env.storage()
+                .instance()
+                .set(&RecoveryDataKey::Guardians(vault_id), &config);
+        }
+
+        // Emit VaultTransferred event
+        VaultTransferred {
+            vault_id,
+            from: old_owner,
+            to: new_address,
+            kind: TransferKind::KeyRotation,
+        }
+        .publish(&env);
+    }
+
+    // Set the default staking adapter (registering it if needed)
+    pub fn set_staking_contract(env: Env, contract: Address) {
+        Self::require_admin(&env);
+        let mut adapters = Self::staking_adapters(&env);
+        adapters.set(contract.clone(), true);
+        env.storage().instance().set(&StakingDataKey::Adapters, &adapters);
+        env.storage()
+            .instance()
+            .set(&StakingDataKey::DefaultAdapter, &contract);
+
+        StakingAdapterAdded {
+            adapter: contract,
+            is_default: true,
+        }
+        .publish(&env);
+    }
+
+    // Admin-only: approve a staking adapter
+    pub fn add_staking_adapter(env: Env, adapter: Address) {
+        Self::require_admin(&env);
+        let mut adapters = Self::staking_adapters(&env);
+        adapters.set(adapter.clone(), true);
+        env.storage().instance().set(&StakingDataKey::Adapters, &adapters);
+
+        StakingAdapterAdded {
+            adapter,
+            is_default: false,
+        }
+        .publish(&env);
+    }
+
+    // Admin-only: delist a staking adapter. Vaults already staked with it can
+    // still unstake, but no new stake can be placed with it.
+    pub fn remove_staking_adapter(env: Env, adapter: Address) {
+        Self::require_admin(&env);
+        let mut adapters = Self::staking_adapters(&env);
+        if !adapters.get(adapter.clone()).unwrap_or(false) {
+            panic!("Staking adapter not approved");
+        }
+        adapters.set(adapter.clone(), false);
+        env.storage().instance().set(&StakingDataKey::Adapters, &adapters);
+
+        let default_adapter: Option<Address> =
+            env.storage().instance().get(&StakingDataKey::DefaultAdapter);
+        if default_adapter == Some(adapter.clone()) {
+            env.storage().instance().remove(&StakingDataKey::DefaultAdapter);
+        }
+
+        StakingAdapterRemoved { adapter }.publish(&env);
+    }
+
+    // Get all currently approved staking adapters
+    pub fn get_staking_adapters(env: Env) -> Vec<Address> {
+        let mut approved = Vec::new(&env);
+        for (adapter, active) in Self::staking_adapters(&env).iter() {
+            if active {
+                approved.push_back(adapter);
+            }
+        }
+        approved
+    }
+
+    // Choose the staking adapter for a vault (only while nothing is staked)
+    pub fn set_vault_adapter(env: Env, vault_id: u64, adapter: Address) {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| {
+                panic!("Vault not found");
+            });
+
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+
+        let caller = env.current_contract_address();
+        if caller != vault.owner {
+            panic!("Only vault owner can set adapter");
+        }
+
+        if vault.staked_amount > 0 {
+            panic!("Unstake before switching adapter");
+        }
+        Self::require_active_adapter(&env, &adapter);
+
+        env.storage()
+            .instance()
+            .set(&StakingDataKey::VaultAdapter(vault_id), &adapter);
+
+        VaultAdapterSet { vault_id, adapter }.publish(&env);
+    }
+
+    // Get the adapter a vault stakes with (its own choice or the default)
+    pub fn get_vault_adapter(env: Env, vault_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&StakingDataKey::VaultAdapter(vault_id))
+            .or(env.storage().instance().get(&StakingDataKey::DefaultAdapter))
+    }
+
+    // Balance reported by the vault's adapter
+    pub fn get_staked_balance(env: Env, vault_id: u64) -> i128 {
+        let adapter = Self::vault_adapter(&env, vault_id);
+        StakingAdapterClient::new(&env, &adapter).balance_of(&vault_id)
+    }
+
+    // Rewards pending at the vault's adapter
+    pub fn get_pending_rewards(env: Env, vault_id: u64) -> i128 {
+        let adapter = Self::vault_adapter(&env, vault_id);
+        StakingAdapterClient::new(&env, &adapter).pending_rewards(&vault_id)
+    }
+
+    fn staking_adapters(env: &Env) -> Map<Address, bool> {
+        env.storage()
+            .instance()
+            .get(&StakingDataKey::Adapters)
+            .unwrap_or(Map::new(env))
+    }
+
+    fn require_active_adapter(env: &Env, adapter: &Address) {
+        if !Self::staking_adapters(env).get(adapter.clone()).unwrap_or(false) {
+            panic!("Staking adapter not approved");
+        }
+    }
 
-        vault.total_amount
+    // Internal helper: resolve the adapter for a vault, panicking if none is configured
+    fn vault_adapter(env: &Env, vault_id: u64) -> Address {
+        Self::get_vault_adapter(env.clone(), vault_id)
+            .unwrap_or_else(|| panic!("Staking adapter not set"))
     }
 
-    // Transfer vault ownership to another beneficiary (if transferable)
-    pub fn transfer_vault(env: Env, vault_id: u64, new_beneficiary: Address) {
+    // Stake unvested tokens to the whitelisted staking contract
+    pub fn stake_tokens(env: Env, vault_id: u64, caller: Address, amount: i128, validator: Address) {
+        caller.require_auth();
+
         let mut vault: Vault = env
             .storage()
             .instance()
@@ -1117,64 +3543,56 @@ impl VestingContract {
             panic!("Vault not initialized");
         }
 
-        if !vault.is_transferable {
-            panic!("Vault is non-transferable");
-        }
-
-        // Check if caller is the vault owner
-        let caller = env.current_contract_address();
+        // The owner, or a delegate holding the Stake scope
         if caller != vault.owner {
-            panic!("Only vault owner can transfer");
+            Self::require_delegate_scope(&env, vault_id, &caller, DelegateScope::Stake);
         }
 
-        let old_owner = vault.owner.clone();
-
-        // Update UserVaults
-        // Remove from old owner
-        let mut old_user_vaults: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserVaults(old_owner.clone()))
-            .unwrap_or(Vec::new(&env));
-        
-        let mut new_old_user_vaults = Vec::new(&env);
-        for id in old_user_vaults.iter() {
-            if id != vault_id {
-                new_old_user_vaults.push_back(id);
-            }
+        // Check available balance (total - released - staked - unbonding)
+        let unbonding = Self::settle_unbonding(&env, vault_id);
+        let available =
+            vault.total_amount - vault.released_amount - vault.staked_amount - unbonding;
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        if amount > available {
+            panic!("Insufficient funds to stake");
         }
-        env.storage()
-            .instance()
-            .set(&DataKey::UserVaults(old_owner.clone()), &new_old_user_vaults);
 
-        // Add to new owner
-        let mut new_user_vaults: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserVaults(new_beneficiary.clone()))
-            .unwrap_or(Vec::new(&env));
-        new_user_vaults.push_back(vault_id);
+        // New stake may only go to an approved adapter; pin it to the vault so
+        // later changes to the default adapter cannot strand the position
+        let adapter = Self::vault_adapter(&env, vault_id);
+        Self::require_active_adapter(&env, &adapter);
         env.storage()
             .instance()
-            .set(&DataKey::UserVaults(new_beneficiary.clone()), &new_user_vaults);
+            .set(&StakingDataKey::VaultAdapter(vault_id), &adapter);
+        StakingAdapterClient::new(&env, &adapter).stake(&vault_id, &amount, &validator);
 
-        // Update vault
-        vault.owner = new_beneficiary.clone();
-        vault.delegate = None; // Reset delegate on transfer
-        
+        // Track the stake per validator
+        let mut validators = Self::get_vault_validators(env.clone(), vault_id);
+        let current = validators.get(validator.clone()).unwrap_or(0);
+        validators.set(validator.clone(), current + amount);
         env.storage()
             .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+            .set(&StakingDataKey::VaultValidators(vault_id), &validators);
 
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "BeneficiaryUpdated"), vault_id),
-            (old_owner, new_beneficiary),
-        );
+        // Update vault state
+        vault.staked_amount += amount;
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+
+        TokensStaked {
+            vault_id,
+            validator,
+            amount,
+        }
+        .publish(&env);
     }
 
-    // Rotate beneficiary key (security feature, allows self-transfer even if non-transferable)
-    pub fn rotate_beneficiary_key(env: Env, vault_id: u64, new_address: Address) {
+    // Unstake tokens from the staking contract. With an unbonding period set,
+    // the tokens only become claimable once the period has elapsed.
+    pub fn unstake_tokens(env: Env, vault_id: u64, caller: Address, amount: i128, validator: Address) {
+        caller.require_auth();
+
         let mut vault: Vault = env
             .storage()
             .instance()
@@ -1187,64 +3605,58 @@ impl VestingContract {
             panic!("Vault not initialized");
         }
 
-        // Require authorization from the current owner
-        vault.owner.require_auth();
-
-        let old_owner = vault.owner.clone();
+        if caller != vault.owner {
+            Self::require_delegate_scope(&env, vault_id, &caller, DelegateScope::Stake);
+        }
 
-        // Update UserVaults
-        // Remove from old owner
-        let mut old_user_vaults: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserVaults(old_owner.clone()))
-            .unwrap_or(Vec::new(&env));
-        
-        let mut new_old_user_vaults = Vec::new(&env);
-        for id in old_user_vaults.iter() {
-            if id != vault_id {
-                new_old_user_vaults.push_back(id);
-            }
+        if amount <= 0 {
+            panic!("Amount must be positive");
         }
-        env.storage()
-            .instance()
-            .set(&DataKey::UserVaults(old_owner.clone()), &new_old_user_vaults);
 
-        // Add to new owner
-        let mut new_user_vaults: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserVaults(new_address.clone()))
-            .unwrap_or(Vec::new(&env));
-        new_user_vaults.push_back(vault_id);
-        env.storage()
-            .instance()
-            .set(&DataKey::UserVaults(new_address.clone()), &new_user_vaults);
+        // Only the stake held by the named validator is released
+        Self::release_validator_stake(&env, vault_id, &validator, amount);
 
-        // Update vault
-        vault.owner = new_address.clone();
-        vault.delegate = None; // Reset delegate on rotation for security
-        
-        env.storage()
-            .instance()
-            .set(&DataKey::VaultData(vault_id), &vault);
+        // Unstaking stays possible even if the adapter has been delisted
+        let adapter = Self::vault_adapter(&env, vault_id);
+        StakingAdapterClient::new(&env, &adapter).unstake(&vault_id, &amount, &validator);
 
-        // Emit BeneficiaryRotated event
-        env.events().publish(
-            (Symbol::new(&env, "BeneficiaryRotated"), vault_id),
-            (old_owner, new_address),
-        );
-    }
+        vault.staked_amount -= amount;
+        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
 
-    // Set the whitelisted staking contract address
-    pub fn set_staking_contract(env: Env, contract: Address) {
-        Self::require_admin(&env);
-        env.storage().instance().set(&Symbol::new(&env, "StakingContract"), &contract);
+        // Queue the tokens behind the unbonding period (if any)
+        let now = env.ledger().timestamp();
+        let release_time = now + Self::get_unbonding_period(env.clone());
+        if release_time > now {
+            let mut entries = Self::get_unbonding_entries(env.clone(), vault_id);
+            entries.push_back(UnbondingEntry {
+                amount,
+                release_time,
+            });
+            env.storage()
+                .instance()
+                .set(&StakingDataKey::VaultUnbonding(vault_id), &entries);
+        }
+
+        TokensUnstaked {
+            vault_id,
+            amount,
+            release_time,
+        }
+        .publish(&env);
     }
 
-    // Stake unvested tokens to the whitelisted staking contract
-    pub fn stake_tokens(env: Env, vault_id: u64, amount: i128, validator: Address) {
-        let mut vault: Vault = env
+    // Move stake from one validator to another without unbonding
+    pub fn redelegate(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        from_validator: Address,
+        to_validator: Address,
+        amount: i128,
+    ) {
+        caller.require_auth();
+
+        let vault: Vault = env
             .storage()
             .instance()
             .get(&DataKey::VaultData(vault_id))
@@ -1256,33 +3668,128 @@ impl VestingContract {
             panic!("Vault not initialized");
         }
 
-        // Check auth (owner or delegate?) - usually owner
-        let caller = env.current_contract_address();
         if caller != vault.owner {
-            panic!("Only vault owner can stake");
+            Self::require_delegate_scope(&env, vault_id, &caller, DelegateScope::Stake);
         }
 
-        // Check available balance (total - released - staked)
-        let available = vault.total_amount - vault.released_amount - vault.staked_amount;
         if amount <= 0 {
             panic!("Amount must be positive");
         }
-        if amount > available {
-            panic!("Insufficient funds to stake");
+        if from_validator == to_validator {
+            panic!("Validators must differ");
         }
 
-        // Get staking contract
-        let staking_contract: Address = env.storage().instance()
-            .get(&Symbol::new(&env, "StakingContract"))
-            .expect("Staking contract not set");
+        let mut validators = Self::get_vault_validators(env.clone(), vault_id);
+        let from_stake = validators.get(from_validator.clone()).unwrap_or(0);
+        if amount > from_stake {
+            panic!("Amount exceeds stake with validator");
+        }
 
-        // Call stake on external contract
-        let args = vec![&env, vault_id.into_val(&env), amount.into_val(&env), validator.into_val(&env)];
-        env.invoke_contract::<()>(&staking_contract, &Symbol::new(&env, "stake"), args);
+        // Unstake from the old validator and stake with the new one
+        let adapter = Self::vault_adapter(&env, vault_id);
+        Self::require_active_adapter(&env, &adapter);
+        let adapter_client = StakingAdapterClient::new(&env, &adapter);
+        adapter_client.unstake(&vault_id, &amount, &from_validator);
+        adapter_client.stake(&vault_id, &amount, &to_validator);
 
-        // Update vault state
-        vault.staked_amount += amount;
-        env.storage().instance().set(&DataKey::VaultData(vault_id), &vault);
+        if from_stake == amount {
+            validators.remove(from_validator.clone());
+        } else {
+            validators.set(from_validator.clone(), from_stake - amount);
+        }
+        let to_stake = validators.get(to_validator.clone()).unwrap_or(0);
+        validators.set(to_validator.clone(), to_stake + amount);
+        env.storage()
+            .instance()
+            .set(&StakingDataKey::VaultValidators(vault_id), &validators);
+
+        Redelegated {
+            vault_id,
+            from_validator,
+            to_validator,
+            amount,
+        }
+        .publish(&env);
+    }
+
+    // Admin-only: set how long unstaked tokens wait before becoming claimable
+    pub fn set_unbonding_period(env: Env, period: u64) {
+        Self::require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&StakingDataKey::UnbondingPeriod, &period);
+
+        UnbondingPeriodSet { period }.publish(&env);
+    }
+
+    pub fn get_unbonding_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&StakingDataKey::UnbondingPeriod)
+            .unwrap_or(0)
+    }
+
+    // Get the amount staked with each validator for a vault
+    pub fn get_vault_validators(env: Env, vault_id: u64) -> Map<Address, i128> {
+        env.storage()
+            .instance()
+            .get(&StakingDataKey::VaultValidators(vault_id))
+            .unwrap_or(Map::new(&env))
+    }
+
+    // Get the unbonding entries for a vault (including matured ones not yet settled)
+    pub fn get_unbonding_entries(env: Env, vault_id: u64) -> Vec<UnbondingEntry> {
+        env.storage()
+            .instance()
+            .get(&StakingDataKey::VaultUnbonding(vault_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Internal helper: drop matured unbonding entries and return the amount still unbonding
+    fn settle_unbonding(env: &Env, vault_id: u64) -> i128 {
+        let entries = Self::get_unbonding_entries(env.clone(), vault_id);
+        if entries.is_empty() {
+            return 0;
+        }
+
+        let now = env.ledger().timestamp();
+        let mut pending = Vec::new(env);
+        let mut pending_amount: i128 = 0;
+        for entry in entries.iter() {
+            if entry.release_time > now {
+                pending_amount += entry.amount;
+                pending.push_back(entry);
+            }
+        }
+
+        if pending.is_empty() {
+            env.storage()
+                .instance()
+                .remove(&StakingDataKey::VaultUnbonding(vault_id));
+        } else if pending.len() != entries.len() {
+            env.storage()
+                .instance()
+                .set(&StakingDataKey::VaultUnbonding(vault_id), &pending);
+        }
+
+        pending_amount
+    }
+
+    // Internal helper: reduce the stake recorded for `validator` by `amount`
+    fn release_validator_stake(env: &Env, vault_id: u64, validator: &Address, amount: i128) {
+        let mut validators = Self::get_vault_validators(env.clone(), vault_id);
+        let staked = validators.get(validator.clone()).unwrap_or(0);
+        if amount > staked {
+            panic!("Amount exceeds stake with validator");
+        }
+        if staked == amount {
+            validators.remove(validator.clone());
+        } else {
+            validators.set(validator.clone(), staked - amount);
+        }
+        env.storage()
+            .instance()
+            .set(&StakingDataKey::VaultValidators(vault_id), &validators);
     }
 
     // Mark a vault as irrevocable to prevent admin withdrawal
@@ -1310,10 +3817,11 @@ impl VestingContract {
 
         // Emit IrrevocableMarked event
         let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (Symbol::new(&env, "IrrevocableMarked"), vault_id),
+        IrrevocableMarked {
+            vault_id,
             timestamp,
-        );
+        }
+        .publish(&env);
     }
 
     // Check if a vault is irrevocable
@@ -1377,11 +3885,13 @@ impl VestingContract {
 
     // Calculate currently claimable tokens based on linear vesting
     pub fn get_claimable_amount(env: Env, vault_id: u64) -> i128 {
-        let vault: Vault = env.storage().instance()
-            .get(&VAULT_DATA, &vault_id)
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        let vested = Self::calculate_time_vested_amount(&env, &vault);
+        let vested = Self::vested_amount(&env, vault_id, &vault, env.ledger().timestamp());
 
         if vested > vault.released_amount {
             vested - vault.released_amount
@@ -1390,42 +3900,275 @@ impl VestingContract {
         }
     }
 
+    // Vested amount of a vault at each of the given timestamps, using the same math as claims
+    pub fn preview_schedule(env: Env, vault_id: u64, timestamps: Vec<u64>) -> Vec<i128> {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        let mut amounts = Vec::new(&env);
+        for timestamp in timestamps.iter() {
+            amounts.push_back(Self::vested_amount(&env, vault_id, &vault, timestamp));
+        }
+        amounts
+    }
+
+    // Tokens that vest after `t0` up to and including `t1`, using the same math
+    // as claims. Reporting periods are built from this, so adjacent ranges
+    // never count a tranche twice.
+    pub fn vested_between(env: Env, vault_id: u64, t0: u64, t1: u64) -> i128 {
+        if t1 < t0 {
+            panic!("t1 must not be before t0");
+        }
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        Self::vested_amount(&env, vault_id, &vault, t1)
+            - Self::vested_amount(&env, vault_id, &vault, t0)
+    }
+
+    // Next timestamp at which the vested amount increases, or None if it never
+    // will on its own (fully vested, or milestone vaults waiting on the admin)
+    pub fn next_unlock(env: Env, vault_id: u64) -> Option<u64> {
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"));
+
+        if env.storage().instance().has(&DataKey::VaultMilestones(vault_id))
+            || vault.suspended_at.is_some()
+        {
+            return None;
+        }
+
+        let now = env.ledger().timestamp();
+        let vested = Self::calculate_time_vested_amount(&vault, now);
+        if vested >= vault.total_amount
+            || Self::vested_amount(&env, vault_id, &vault, now) >= vault.total_amount
+        {
+            return None;
+        }
+
+        // Smallest elapsed time e with total * e / duration > vested,
+        // rounded up to a whole step for step vesting
+        let duration = (vault.end_time - vault.start_time - vault.suspended_duration) as i128;
+        let target = vested + 1;
+        let mut elapsed = ((target * duration + vault.total_amount - 1) / vault.total_amount) as u64;
+        if vault.step_duration > 0 {
+            elapsed = elapsed.div_ceil(vault.step_duration) * vault.step_duration;
+        }
+
+        let unlock = vault.start_time + vault.suspended_duration + elapsed;
+        if unlock > vault.end_time {
+            Some(vault.end_time)
+        } else {
+            Some(unlock)
+        }
+    }
+
     // Auto-claim function that anyone can call.
     // Tokens go to beneficiary, but keeper can get a tip.
     pub fn auto_claim(env: Env, vault_id: u64, keeper: Address) {
-        let mut vault: Vault = env.storage().instance()
-            .get(&VAULT_DATA, &vault_id)
+        let vault: Vault = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
             .unwrap_or_else(|| panic!("Vault not found"));
 
-        require!(vault.is_initialized, "Vault not initialized");
+        // Anyone may keep a vault, but one of its delegates needs the SetAutoClaim scope
+        if Self::get_delegates(env.clone(), vault_id).contains_key(keeper.clone()) {
+            Self::require_delegate_scope(&env, vault_id, &keeper, DelegateScope::SetAutoClaim);
+        }
 
         let claimable = Self::get_claimable_amount(env.clone(), vault_id);
-        
+
         // Ensure there's enough to cover the fee and something left for beneficiary
-        require!(claimable > vault.keeper_fee, "Insufficient claimable tokens to cover fee");
+        if claimable <= vault.keeper_fee {
+            panic!("Insufficient claimable tokens to cover fee");
+        }
 
-        let beneficiary_amount = claimable - vault.keeper_fee;
-        
-        // Update vault
-        vault.released_amount += claimable;
-        env.storage().instance().set(&VAULT_DATA, &vault_id, &vault);
+        // Same vesting, staking and liquidity rules as a regular claim
+        Self::internal_claim(&env, vault_id, claimable);
 
         // Update keeper fees
-        let mut fees: Map<Address, i128> = env.storage().instance().get(&KEEPER_FEES).unwrap_or(Map::new(&env));
+        let mut fees: Map<Address, i128> = env
+            .storage()
+            .instance()
+            .get(&DataKey::KeeperFees)
+            .unwrap_or(Map::new(&env));
         let current_fees = fees.get(keeper.clone()).unwrap_or(0);
         fees.set(keeper.clone(), current_fees + vault.keeper_fee);
-        env.storage().instance().set(&KEEPER_FEES, &fees);
+        env.storage().instance().set(&DataKey::KeeperFees, &fees);
 
-        // Emit KeeperClaim event
-        env.events().publish(
-            (Symbol::new(&env, "KeeperClaim"), vault_id, keeper),
-            (beneficiary_amount, vault.keeper_fee)
-        );
+        let beneficiary_amount = claimable - vault.keeper_fee;
+        Self::distribute_payout(&env, vault_id, &vault.owner, beneficiary_amount);
+
+        // Emit AutoClaimed event
+        AutoClaimed {
+            vault_id,
+            keeper,
+            beneficiary_amount,
+            keeper_fee: vault.keeper_fee,
+        }
+        .publish(&env);
     }
 
     // Get accumulated fees for a keeper
     pub fn get_keeper_fee(env: Env, keeper: Address) -> i128 {
-        let fees: Map<Address, i128> = env.storage().instance().get(&KEEPER_FEES).unwrap_or(Map::new(&env));
+        let fees: Map<Address, i128> = env
+            .storage()
+            .instance()
+            .get(&DataKey::KeeperFees)
+            .unwrap_or(Map::new(&env));
         fees.get(keeper).unwrap_or(0)
     }
+
+    // --- Governance Voting Power ---
+
+    // Delegate the voting power of all of `owner`'s vaults to `delegatee`.
+    // This is independent of the claim `delegate` set on individual vaults.
+    pub fn delegate_votes(env: Env, owner: Address, delegatee: Address) {
+        owner.require_auth();
+
+        let old_delegatee = Self::get_vote_delegate(env.clone(), owner.clone());
+        if old_delegatee == delegatee {
+            panic!("Votes already delegated to this address");
+        }
+
+        let locked: i128 = env
+            .storage()
+            .instance()
+            .get(&VotingDataKey::LockedBalance(owner.clone()))
+            .unwrap_or(0);
+        Self::write_voting_power(&env, &old_delegatee, -locked);
+        Self::write_voting_power(&env, &delegatee, locked);
+
+        if delegatee == owner {
+            env.storage()
+                .instance()
+                .remove(&VotingDataKey::VoteDelegate(owner.clone()));
+        } else {
+            env.storage()
+                .instance()
+                .set(&VotingDataKey::VoteDelegate(owner.clone()), &delegatee);
+        }
+
+        VoteDelegateChanged {
+            owner,
+            old_delegatee,
+            new_delegatee: delegatee,
+        }
+        .publish(&env);
+    }
+
+    // Get the address currently voting with `owner`'s locked balance
+    pub fn get_vote_delegate(env: Env, owner: Address) -> Address {
+        env.storage()
+            .instance()
+            .get(&VotingDataKey::VoteDelegate(owner.clone()))
+            .unwrap_or(owner)
+    }
+
+    // Current voting power: locked tokens of vaults owned by (or vote-delegated to) the address
+    pub fn voting_power(env: Env, address: Address) -> i128 {
+        match Self::checkpoint_count(&env, &address) {
+            0 => 0,
+            count => Self::checkpoint(&env, &address, count - 1).power,
+        }
+    }
+
+    // Voting power as of the end of ledger `ledger`
+    pub fn voting_power_at(env: Env, address: Address, ledger: u32) -> i128 {
+        // Binary search for the last checkpoint at or before `ledger`
+        let mut low: u32 = 0;
+        let mut high: u32 = Self::checkpoint_count(&env, &address);
+        while low < high {
+            let mid = (low + high) / 2;
+            if Self::checkpoint(&env, &address, mid).ledger <= ledger {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            0
+        } else {
+            Self::checkpoint(&env, &address, low - 1).power
+        }
+    }
+
+    fn checkpoint_count(env: &Env, address: &Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&VotingDataKey::CheckpointCount(address.clone()))
+            .unwrap_or(0)
+    }
+
+    fn checkpoint(env: &Env, address: &Address, index: u32) -> VotingCheckpoint {
+        env.storage()
+            .persistent()
+            .get(&VotingDataKey::Checkpoint(address.clone(), index))
+            .unwrap()
+    }
+
+    // Internal helper: record a change in `owner`'s locked balance and update the votes it carries
+    fn adjust_locked_balance(env: &Env, owner: &Address, delta: i128) {
+        if delta == 0 {
+            return;
+        }
+        let locked: i128 = env
+            .storage()
+            .instance()
+            .get(&VotingDataKey::LockedBalance(owner.clone()))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&VotingDataKey::LockedBalance(owner.clone()), &(locked + delta));
+
+        let delegatee = Self::get_vote_delegate(env.clone(), owner.clone());
+        Self::write_voting_power(env, &delegatee, delta);
+    }
+
+    // Internal helper: move locked balance between owners when a vault changes hands
+    fn move_locked_balance(env: &Env, from: &Address, to: &Address, amount: i128) {
+        Self::adjust_locked_balance(env, from, -amount);
+        Self::adjust_locked_balance(env, to, amount);
+    }
+
+    // Internal helper: append (or update this ledger's) voting power checkpoint
+    fn write_voting_power(env: &Env, account: &Address, delta: i128) {
+        if delta == 0 {
+            return;
+        }
+        let count = Self::checkpoint_count(env, account);
+        let last = match count {
+            0 => None,
+            _ => Some(Self::checkpoint(env, account, count - 1)),
+        };
+
+        let ledger = env.ledger().sequence();
+        let (index, power) = match last {
+            Some(last) if last.ledger == ledger => (count - 1, last.power + delta),
+            Some(last) => (count, last.power + delta),
+            None => (0, delta),
+        };
+        env.storage().persistent().set(
+            &VotingDataKey::Checkpoint(account.clone(), index),
+            &VotingCheckpoint { ledger, power },
+        );
+        if index == count {
+            env.storage()
+                .persistent()
+                .set(&VotingDataKey::CheckpointCount(account.clone()), &(count + 1));
+        }
+    }
 }
+
+#[cfg(test)]
+mod test;
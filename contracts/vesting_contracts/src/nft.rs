@@ -0,0 +1,167 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String};
+
+use crate::{
+    DataKey, NftApprove, Vault, VestingContract, VestingContractArgs, VestingContractClient,
+};
+
+// DataKey for the NFT view of vault positions
+#[contracttype]
+pub enum NftDataKey {
+    Approved(u64), // Address allowed to transfer the vault on the owner's behalf
+}
+
+// Longest token_uri we build: fixed JSON keys plus an escaped 32-byte title and
+// five numbers
+const TOKEN_URI_CAPACITY: usize = 320;
+
+/// NFT-style interface over vesting vaults: the token id is the vault id.
+/// Event names follow the usual NFT conventions so wallets and marketplaces
+/// can index them.
+#[contractimpl]
+#[allow(deprecated)]
+impl VestingContract {
+    pub fn owner_of(env: Env, token_id: u64) -> Address {
+        Self::load_vault(&env, token_id).owner
+    }
+
+    // Number of vaults held by `owner`, lazy ones included
+    pub fn balance_of(env: Env, owner: Address) -> u32 {
+        Self::get_user_vaults(env, owner).len()
+    }
+
+    // Owner-only: let `approved` transfer the vault once (None clears it)
+    pub fn approve(env: Env, token_id: u64, approved: Option<Address>) {
+        let vault = Self::load_vault(&env, token_id);
+        vault.owner.require_auth();
+
+        match &approved {
+            Some(address) => env
+                .storage()
+                .instance()
+                .set(&NftDataKey::Approved(token_id), address),
+            None => env
+                .storage()
+                .instance()
+                .remove(&NftDataKey::Approved(token_id)),
+        }
+
+        NftApprove {
+            owner: vault.owner,
+            token_id,
+            approved,
+        }
+        .publish(&env);
+    }
+
+    pub fn approved(env: Env, token_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&NftDataKey::Approved(token_id))
+    }
+
+    // Start a transfer of a transferable vault. `spender` is the owner or its
+    // approved address. Like transfer_vault, ownership only moves once `to`
+    // calls accept_vault_transfer; any approval is cleared then.
+    pub fn transfer(env: Env, spender: Address, to: Address, token_id: u64) {
+        spender.require_auth();
+
+        let vault = Self::load_vault(&env, token_id);
+        if !vault.is_initialized {
+            panic!("Vault not initialized");
+        }
+        if !vault.is_transferable {
+            panic!("Vault is non-transferable");
+        }
+        if spender != vault.owner && Self::approved(env.clone(), token_id) != Some(spender) {
+            panic!("Caller is not owner or approved");
+        }
+
+        Self::request_transfer(&env, token_id, &vault, &to);
+    }
+
+    // Wallet metadata as a JSON data URI, named after the vault title
+    pub fn token_uri(env: Env, token_id: u64) -> String {
+        let vault = Self::load_vault(&env, token_id);
+
+        let mut uri = UriWriter::new();
+        uri.push(b"data:application/json;utf8,{\"name\":\"");
+        if vault.title.is_empty() {
+            uri.push(b"Vesting Vault #");
+            uri.push_u128(token_id as u128);
+        } else {
+            let mut title = [0u8; 32];
+            let len = vault.title.len() as usize;
+            vault.title.copy_into_slice(&mut title[..len]);
+            uri.push_escaped(&title[..len]);
+        }
+        uri.push(b"\",\"vault_id\":");
+        uri.push_u128(token_id as u128);
+        uri.push(b",\"total_amount\":\"");
+        uri.push_u128(vault.total_amount as u128);
+        uri.push(b"\",\"released_amount\":\"");
+        uri.push_u128(vault.released_amount as u128);
+        uri.push(b"\",\"start_time\":");
+        uri.push_u128(vault.start_time as u128);
+        uri.push(b",\"end_time\":");
+        uri.push_u128(vault.end_time as u128);
+        uri.push(b"}");
+
+        String::from_bytes(&env, uri.as_bytes())
+    }
+
+    fn load_vault(env: &Env, vault_id: u64) -> Vault {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultData(vault_id))
+            .unwrap_or_else(|| panic!("Vault not found"))
+    }
+}
+
+// Fixed-size buffer for building token_uri without an allocator
+struct UriWriter {
+    buf: [u8; TOKEN_URI_CAPACITY],
+    len: usize,
+}
+
+impl UriWriter {
+    fn new() -> Self {
+        UriWriter {
+            buf: [0u8; TOKEN_URI_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    // Titles are free text; escape what would break the JSON string
+    fn push_escaped(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            match b {
+                b'"' | b'\\' => self.push(&[b'\\', b]),
+                0..=0x1f => self.push(b" "),
+                _ => self.push(&[b]),
+            }
+        }
+    }
+
+    fn push_u128(&mut self, mut value: u128) {
+        let mut digits = [0u8; 39];
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.push(&digits[i..]);
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Cli(#[from] vesting_cli::Error),
+
+    #[error("index: {0}")]
+    Index(#[from] vesting_indexer::Error),
+
+    #[error("prices: {0}")]
+    Prices(String),
+
+    #[error("vested_between failed for vault {0}")]
+    Contract(u64),
+
+    #[error("fair value of vault {0} does not fit in 128 bits")]
+    Overflow(u64),
+
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+}
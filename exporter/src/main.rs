@@ -0,0 +1,20 @@
+use std::process::ExitCode;
+
+use vesting_exporter::Error;
+
+fn main() -> ExitCode {
+    match vesting_exporter::run(std::env::args().skip(1)) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(Error::Usage(message) | Error::Cli(vesting_cli::Error::Usage(message))) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
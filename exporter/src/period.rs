@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Months, NaiveDate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Granularity {
+    Month,
+    Quarter,
+}
+
+impl Granularity {
+    fn months(self) -> u32 {
+        match self {
+            Granularity::Month => 1,
+            Granularity::Quarter => 3,
+        }
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        match text {
+            "month" => Ok(Granularity::Month),
+            "quarter" => Ok(Granularity::Quarter),
+            _ => Err("expected month or quarter".into()),
+        }
+    }
+}
+
+/// A reporting period, `[start, end)` in unix seconds (UTC).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Period {
+    /// `2025-01` for a month, `2025-Q1` for a quarter
+    pub label: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Whole periods from the one containing `from` to the one containing the
+/// day before `to`.
+pub fn periods(from: NaiveDate, to: NaiveDate, granularity: Granularity) -> Vec<Period> {
+    let step = granularity.months();
+    let first_month = (from.month0() / step) * step + 1;
+    let mut start = NaiveDate::from_ymd_opt(from.year(), first_month, 1).expect("first of month");
+
+    let mut periods = Vec::new();
+    while start < to {
+        let end = start + Months::new(step);
+        let label = match granularity {
+            Granularity::Month => start.format("%Y-%m").to_string(),
+            Granularity::Quarter => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+        };
+        periods.push(Period {
+            label,
+            start: timestamp(start),
+            end: timestamp(end),
+        });
+        start = end;
+    }
+    periods
+}
+
+// Midnight UTC, clamped to the ledger's epoch
+pub(crate) fn timestamp(date: NaiveDate) -> u64 {
+    let seconds = date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight")
+        .and_utc()
+        .timestamp();
+    u64::try_from(seconds).unwrap_or(0)
+}
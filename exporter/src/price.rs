@@ -0,0 +1,112 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::period::timestamp;
+use crate::Error;
+
+/// A decimal price per whole token, kept exact as `units / 10^scale`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    units: i128,
+    scale: u32,
+}
+
+impl Price {
+    /// Value of `amount` base units, in hundredths of the price currency,
+    /// rounded half up. `decimals` is the token's decimals.
+    pub fn cents(self, amount: i128, decimals: u32) -> Option<i128> {
+        let exact = amount.checked_mul(self.units)?;
+        let scale = decimals + self.scale;
+        if scale <= 2 {
+            return exact.checked_mul(10i128.checked_pow(2 - scale)?);
+        }
+        let divisor = 10i128.checked_pow(scale - 2)?;
+        Some((exact + divisor / 2) / divisor)
+    }
+}
+
+impl FromStr for Price {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let digits = format!("{whole}{fraction}");
+        if whole.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("{text} is not a decimal price"));
+        }
+        Ok(Price {
+            units: digits
+                .parse()
+                .map_err(|_| format!("{text} has too many digits"))?,
+            scale: fraction.len() as u32,
+        })
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = 10i128.pow(self.scale);
+        write!(f, "{}", self.units / scale)?;
+        if self.scale > 0 {
+            write!(
+                f,
+                ".{:0width$}",
+                self.units % scale,
+                width = self.scale as usize
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format hundredths as `12.34`.
+pub fn format_cents(cents: i128) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    date: NaiveDate,
+    price: String,
+}
+
+/// Token prices over time for the fair-value columns. Each price holds from
+/// its date (midnight UTC) until the next one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Prices {
+    points: Vec<(u64, Price)>,
+}
+
+impl Prices {
+    /// Read a JSON array of `{"date": "2025-01-01", "price": "0.42"}`.
+    /// Prices are strings so they stay exact.
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        let entries: Vec<Entry> = serde_json::from_str(text)?;
+        let mut points = entries
+            .into_iter()
+            .map(|entry| Ok((timestamp(entry.date), entry.price.parse()?)))
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(Error::Prices)?;
+        points.sort_by_key(|(time, _)| *time);
+        Ok(Prices { points })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The price in effect at `time`, if any was set by then
+    pub fn at(&self, time: u64) -> Option<Price> {
+        self.points
+            .iter()
+            .take_while(|(since, _)| *since <= time)
+            .last()
+            .map(|(_, price)| *price)
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Serialize, Serializer};
+use vesting_indexer::{Movement, MovementKind, Store, VaultRecord};
+
+use crate::price::format_cents;
+use crate::{Error, Period, Prices, Schedule};
+
+/// One vault over one period. Amounts are token base units; fair values are
+/// in the price currency and empty when no price was in effect.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StatementRow {
+    pub period: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub beneficiary: String,
+    pub vault_id: u64,
+    pub title: String,
+    #[serde(serialize_with = "as_text")]
+    pub vested: i128,
+    #[serde(serialize_with = "as_text")]
+    pub claimed: i128,
+    #[serde(serialize_with = "as_text")]
+    pub revoked: i128,
+    /// Vested by the end of the period
+    #[serde(serialize_with = "as_text")]
+    pub vested_to_date: i128,
+    /// Still to vest at the end of the period
+    #[serde(serialize_with = "as_text")]
+    pub unvested: i128,
+    /// Price at the end of the period
+    pub price: Option<String>,
+    /// `vested` at `price`
+    pub vested_value: Option<String>,
+    /// Each claim at the price on its date
+    pub claimed_value: Option<String>,
+}
+
+/// Column order of the CSV export
+pub const COLUMNS: [&str; 14] = [
+    "period",
+    "period_start",
+    "period_end",
+    "beneficiary",
+    "vault_id",
+    "title",
+    "vested",
+    "claimed",
+    "revoked",
+    "vested_to_date",
+    "unvested",
+    "price",
+    "vested_value",
+    "claimed_value",
+];
+
+// JSON numbers lose precision past 53 bits
+fn as_text<S: Serializer>(amount: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&amount.to_string())
+}
+
+/// Build statements for every indexed vault of `contract_id`, grouped by
+/// beneficiary. Claims and revocations come from the index; vesting comes
+/// from `schedule`, which follows each vault's current terms, so periods
+/// before a revocation show what the reduced schedule would have vested.
+/// Vaults merged away are no longer indexed and are left out.
+pub fn statements(
+    store: &Store,
+    contract_id: &str,
+    schedule: &impl Schedule,
+    periods: &[Period],
+    prices: &Prices,
+    decimals: u32,
+) -> Result<Vec<StatementRow>, Error> {
+    let mut vaults = store.vaults(contract_id)?;
+    vaults.sort_by(|a, b| (&a.owner, a.vault_id).cmp(&(&b.owner, b.vault_id)));
+
+    let mut rows = Vec::new();
+    for vault in &vaults {
+        let movements = store.movements(contract_id, vault.vault_id)?;
+        for period in periods {
+            if period.end <= vault.creation_time {
+                continue;
+            }
+            rows.push(row(vault, &movements, schedule, period, prices, decimals)?);
+        }
+    }
+    Ok(rows)
+}
+
+fn row(
+    vault: &VaultRecord,
+    movements: &[Movement],
+    schedule: &impl Schedule,
+    period: &Period,
+    prices: &Prices,
+    decimals: u32,
+) -> Result<StatementRow, Error> {
+    // vested_between counts the upper bound, so step back a second to cover
+    // [start, end) like everything else
+    let last = period.end - 1;
+    let vested = schedule.vested_between(vault, period.start.saturating_sub(1), last)?;
+    let vested_to_date = schedule.vested_between(vault, 0, last)?;
+
+    let in_period = |kind: MovementKind| {
+        movements.iter().filter(move |movement| {
+            movement.kind == kind && (period.start..period.end).contains(&movement.timestamp)
+        })
+    };
+    let claimed = in_period(MovementKind::Claim).map(|m| m.amount).sum();
+    let revoked = in_period(MovementKind::Revocation).map(|m| m.amount).sum();
+    // What the vault held at the end of the period, before later revocations
+    let revoked_later: i128 = movements
+        .iter()
+        .filter(|m| m.kind == MovementKind::Revocation && m.timestamp >= period.end)
+        .map(|m| m.amount)
+        .sum();
+    let unvested = (vault.total_amount + revoked_later - vested_to_date).max(0);
+
+    let overflow = || Error::Overflow(vault.vault_id);
+    let price = prices.at(last);
+    let vested_value = price
+        .map(|price| price.cents(vested, decimals).ok_or_else(overflow))
+        .transpose()?;
+    let claimed_value = match price {
+        None => None,
+        Some(_) => {
+            let mut cents = 0i128;
+            for claim in in_period(MovementKind::Claim) {
+                let value = match prices.at(claim.timestamp) {
+                    Some(price) => price.cents(claim.amount, decimals).ok_or_else(overflow)?,
+                    None => 0,
+                };
+                cents = cents.checked_add(value).ok_or_else(overflow)?;
+            }
+            Some(cents)
+        }
+    };
+
+    Ok(StatementRow {
+        period: period.label.clone(),
+        period_start: period.start,
+        period_end: period.end,
+        beneficiary: vault.owner.clone(),
+        vault_id: vault.vault_id,
+        title: vault.title.clone(),
+        vested,
+        claimed,
+        revoked,
+        vested_to_date,
+        unvested,
+        price: price.map(|price| price.to_string()),
+        vested_value: vested_value.map(format_cents),
+        claimed_value: claimed_value.map(format_cents),
+    })
+}
+
+/// CSV with a header row, quoting fields that need it.
+pub fn to_csv(rows: &[StatementRow]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields = [
+            row.period.clone(),
+            row.period_start.to_string(),
+            row.period_end.to_string(),
+            row.beneficiary.clone(),
+            row.vault_id.to_string(),
+            row.title.clone(),
+            row.vested.to_string(),
+            row.claimed.to_string(),
+            row.revoked.to_string(),
+            row.vested_to_date.to_string(),
+            row.unvested.to_string(),
+            row.price.clone().unwrap_or_default(),
+            row.vested_value.clone().unwrap_or_default(),
+            row.claimed_value.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| quote(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn to_json(rows: &[StatementRow]) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(rows)?)
+}
@@ -0,0 +1,72 @@
+use vesting_contracts::VestingContractClient;
+use vesting_indexer::VaultRecord;
+
+use crate::Error;
+
+/// Where vested amounts come from.
+pub trait Schedule {
+    /// Tokens of `vault` that vest after `t0` up to and including `t1`.
+    fn vested_between(&self, vault: &VaultRecord, t0: u64, t1: u64) -> Result<i128, Error>;
+}
+
+/// Vesting worked out from the indexed vault row, with the contract's linear,
+/// step and suspension rules. Acceleration is not indexed, so accelerated
+/// vaults need [`ContractSchedule`].
+pub struct IndexedSchedule;
+
+impl Schedule for IndexedSchedule {
+    fn vested_between(&self, vault: &VaultRecord, t0: u64, t1: u64) -> Result<i128, Error> {
+        Ok(vested_at(vault, t1) - vested_at(vault, t0))
+    }
+}
+
+// Same as the contract's calculate_time_vested_amount
+fn vested_at(vault: &VaultRecord, now: u64) -> i128 {
+    if now < vault.start_time {
+        return 0;
+    }
+    let now = vesting_clock(vault, now);
+    let end_time = vault.end_time.saturating_sub(vault.suspended_duration);
+    if now >= end_time || end_time == vault.start_time {
+        return vault.total_amount;
+    }
+    let duration = end_time - vault.start_time;
+    let elapsed = now - vault.start_time;
+    let elapsed = match elapsed.checked_div(vault.step_duration) {
+        Some(steps) => steps * vault.step_duration,
+        None => elapsed,
+    };
+    vault.total_amount * elapsed as i128 / duration as i128
+}
+
+// `now` less the time vesting was suspended, as in the contract's
+// vesting_clock
+fn vesting_clock(vault: &VaultRecord, now: u64) -> u64 {
+    let mut paused = vault.suspended_duration;
+    if let Some(suspended_at) = vault.suspended_at {
+        paused += now.saturating_sub(suspended_at.max(vault.start_time));
+    }
+    now.saturating_sub(paused).max(vault.start_time)
+}
+
+/// Vesting read from the contract's `vested_between` view, so acceleration
+/// counts exactly as it does for claims. The client can point at
+/// a ledger snapshot (see `vesting_cli::Sandbox`).
+pub struct ContractSchedule<'a> {
+    client: VestingContractClient<'a>,
+}
+
+impl<'a> ContractSchedule<'a> {
+    pub fn new(client: VestingContractClient<'a>) -> Self {
+        ContractSchedule { client }
+    }
+}
+
+impl Schedule for ContractSchedule<'_> {
+    fn vested_between(&self, vault: &VaultRecord, t0: u64, t1: u64) -> Result<i128, Error> {
+        match self.client.try_vested_between(&vault.vault_id, &t0, &t1) {
+            Ok(Ok(amount)) => Ok(amount),
+            _ => Err(Error::Contract(vault.vault_id)),
+        }
+    }
+}
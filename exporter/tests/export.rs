@@ -0,0 +1,169 @@
+use chrono::NaiveDate;
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::xdr::ScAddress;
+use soroban_sdk::{Address, Env};
+use vesting_contracts::{VestingContract, VestingContractClient};
+use vesting_exporter::{
+    periods, statements, to_csv, ContractSchedule, Granularity, IndexedSchedule, Price, Prices,
+    StatementRow,
+};
+use vesting_indexer::{RawEvent, Store};
+
+const DAY: u64 = 86_400;
+// 2025-01-01T00:00:00Z
+const JAN_1: u64 = 1_735_689_600;
+
+fn date(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+}
+
+// Feed the events of the last call into the store, giving each call its own
+// ledger so event ids stay unique
+fn record(env: &Env, contract_id: &Address, store: &mut Store) {
+    let ledger = env.ledger().sequence();
+    let timestamp = env.ledger().timestamp();
+    let all = env.events().all().filter_by_contract(contract_id);
+    for (index, event) in all.events().iter().enumerate() {
+        let id = format!("{ledger:010}-{index:010}");
+        let raw = RawEvent::from_xdr(id, ledger, timestamp, event).unwrap();
+        store.apply(&raw).unwrap();
+    }
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+}
+
+#[test]
+fn test_periods() {
+    let months = periods(date("2025-01-15"), date("2025-03-01"), Granularity::Month);
+    let labels: Vec<&str> = months.iter().map(|p| p.label.as_str()).collect();
+    assert_eq!(labels, ["2025-01", "2025-02"]);
+    assert_eq!(months[0].start, JAN_1);
+    assert_eq!(months[0].end, JAN_1 + 31 * DAY);
+    assert_eq!(months[1].start, months[0].end);
+
+    let quarters = periods(date("2024-11-30"), date("2025-04-02"), Granularity::Quarter);
+    let labels: Vec<&str> = quarters.iter().map(|p| p.label.as_str()).collect();
+    assert_eq!(labels, ["2024-Q4", "2025-Q1", "2025-Q2"]);
+    assert_eq!(quarters[1].start, JAN_1);
+}
+
+#[test]
+fn test_prices() {
+    let price: Price = "0.125".parse().unwrap();
+    assert_eq!(price.to_string(), "0.125");
+    // 3.5 tokens of 7 decimals at 0.125 is 0.4375, rounded half up
+    assert_eq!(price.cents(35_000_000, 7), Some(44));
+    assert_eq!("2".parse::<Price>().unwrap().cents(3, 0), Some(600));
+    assert!("1.2.3".parse::<Price>().is_err());
+    assert!(".5".parse::<Price>().is_err());
+
+    let prices = Prices::from_json(
+        r#"[{"date": "2025-02-01", "price": "2"}, {"date": "2025-01-01", "price": "1"}]"#,
+    )
+    .unwrap();
+    assert_eq!(prices.at(JAN_1 - 1), None);
+    assert_eq!(prices.at(JAN_1 + 40 * DAY), Some("2".parse().unwrap()));
+    assert!(Prices::from_json(r#"[{"date": "2025-01-01", "price": "a lot"}]"#).is_err());
+}
+
+#[test]
+fn test_statements() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = JAN_1);
+    let contract_id = env.register(VestingContract, ());
+    let client = VestingContractClient::new(&env, &contract_id);
+    let mut store = Store::open_in_memory().unwrap();
+
+    client.initialize(&contract_id, &1_000_000i128);
+    record(&env, &contract_id, &mut store);
+    // 100 tokens every 30 days for a year: the first steps land on Jan 31,
+    // Mar 2 and Apr 1
+    let stepped = client.create_vault_full(
+        &contract_id,
+        &1200i128,
+        &JAN_1,
+        &(JAN_1 + 360 * DAY),
+        &0i128,
+        &true,
+        &false,
+        &(30 * DAY),
+    );
+    record(&env, &contract_id, &mut store);
+
+    env.ledger().with_mut(|li| li.timestamp = JAN_1 + 45 * DAY);
+    client.claim_tokens(&stepped, &100i128);
+    record(&env, &contract_id, &mut store);
+    let bob = Address::generate(&env);
+    let linear = client.create_vault_full(
+        &bob,
+        &590i128,
+        &(JAN_1 + 31 * DAY),
+        &(JAN_1 + 90 * DAY),
+        &0i128,
+        &true,
+        &false,
+        &0u64,
+    );
+    record(&env, &contract_id, &mut store);
+
+    // Revoking a quarter keeps the steps at 100 tokens each
+    env.ledger().with_mut(|li| li.timestamp = JAN_1 + 73 * DAY);
+    client.revoke_partial(&stepped, &300i128);
+    record(&env, &contract_id, &mut store);
+
+    let contract = ScAddress::from(&contract_id).to_string();
+    let periods = periods(date("2025-01-01"), date("2025-05-01"), Granularity::Month);
+    let prices = Prices::from_json(
+        r#"[{"date": "2025-01-01", "price": "0.5"}, {"date": "2025-02-10", "price": "1.25"}]"#,
+    )
+    .unwrap();
+    let rows = statements(&store, &contract, &IndexedSchedule, &periods, &prices, 0).unwrap();
+
+    let stepped_rows: Vec<&StatementRow> =
+        rows.iter().filter(|row| row.vault_id == stepped).collect();
+    let flows: Vec<(&str, i128, i128, i128, i128, i128)> = stepped_rows
+        .iter()
+        .map(|row| {
+            (
+                row.period.as_str(),
+                row.vested,
+                row.claimed,
+                row.revoked,
+                row.vested_to_date,
+                row.unvested,
+            )
+        })
+        .collect();
+    assert_eq!(
+        flows,
+        [
+            ("2025-01", 100, 0, 0, 100, 1100),
+            ("2025-02", 0, 100, 0, 100, 1100),
+            ("2025-03", 100, 0, 300, 200, 700),
+            ("2025-04", 100, 0, 0, 300, 600),
+        ]
+    );
+    assert_eq!(stepped_rows[0].vested_value.as_deref(), Some("50.00"));
+    assert_eq!(stepped_rows[1].claimed_value.as_deref(), Some("125.00"));
+    assert_eq!(stepped_rows[2].price.as_deref(), Some("1.25"));
+
+    // Bob's vault only shows up from the month it was created in
+    let bob_rows: Vec<&StatementRow> = rows.iter().filter(|row| row.vault_id == linear).collect();
+    assert_eq!(bob_rows[0].period, "2025-02");
+    let vested: i128 = bob_rows.iter().map(|row| row.vested).sum();
+    assert_eq!(vested, 590);
+    assert!(rows.windows(2).all(|pair| {
+        (&pair[0].beneficiary, pair[0].vault_id) <= (&pair[1].beneficiary, pair[1].vault_id)
+    }));
+
+    // The contract's vested_between view agrees with the indexed terms
+    let schedule = ContractSchedule::new(client);
+    assert_eq!(
+        statements(&store, &contract, &schedule, &periods, &prices, 0).unwrap(),
+        rows
+    );
+
+    let csv = to_csv(&rows);
+    assert!(csv.starts_with("period,period_start,period_end,beneficiary,vault_id,title,"));
+    assert_eq!(csv.lines().count(), rows.len() + 1);
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("invalid fixture line {line}: {message}")]
+    Fixture { line: usize, message: String },
+
+    #[error("invalid xdr: {0}")]
+    Xdr(#[from] stellar_xdr::curr::Error),
+
+    #[error("event {event}: {message}")]
+    Decode { event: String, message: String },
+
+    #[error("event {event}: unsupported schema version {version}")]
+    UnsupportedVersion { event: String, version: String },
+
+    #[error("event {event} refers to unknown vault {vault_id}")]
+    UnknownVault { event: String, vault_id: u64 },
+}
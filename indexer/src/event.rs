@@ -0,0 +1,413 @@
+use ethnum::U256;
+use stellar_xdr::curr::ScVal;
+
+use crate::{Error, RawEvent};
+
+/// Event schema version this indexer decodes (second topic of every event)
+pub const SCHEMA_VERSION: &str = "v1";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationPolicy {
+    ForfeitUnvested,
+    LeaverTerms(u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    Admin,
+    Owner,
+    KeyRotation,
+    Recovery,
+}
+
+/// Decoded events that change indexed state. Everything else, including
+/// events from other contracts and the unversioned NFT events, decodes to
+/// `Other` with the event name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VestingEvent {
+    VaultCreated {
+        vault_id: u64,
+        beneficiary: String,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        step_duration: u64,
+        keeper_fee: i128,
+        is_irrevocable: bool,
+        is_transferable: bool,
+        is_initialized: bool,
+        title: String,
+    },
+    VaultInitialized {
+        vault_id: u64,
+    },
+    VaultTitleSet {
+        vault_id: u64,
+        title: String,
+    },
+    IrrevocableMarked {
+        vault_id: u64,
+    },
+    TokensClaimed {
+        vault_id: u64,
+        amount: i128,
+        released_amount: i128,
+    },
+    VaultTransferred {
+        vault_id: u64,
+        to: String,
+        kind: TransferKind,
+    },
+    DelegateUpdated {
+        vault_id: u64,
+        new_delegate: Option<String>,
+    },
+    VaultSplit {
+        vault_id: u64,
+        child_vault_id: u64,
+        new_owner: String,
+        amount: i128,
+    },
+    VaultsMerged {
+        vault_id: u64,
+        merged_vault_id: u64,
+    },
+    ScheduleShortened {
+        vault_id: u64,
+        total_amount: i128,
+        end_time: u64,
+        step_duration: u64,
+        suspended_at: Option<u64>,
+    },
+    VaultClawedBack {
+        vault_id: u64,
+    },
+    RevocationPolicySet {
+        vault_id: u64,
+        policy: RevocationPolicy,
+    },
+    VaultToppedUp {
+        vault_id: u64,
+        total_amount: i128,
+        end_time: u64,
+    },
+    ScheduleAmended {
+        vault_id: u64,
+        start_time: u64,
+        end_time: u64,
+        step_duration: u64,
+    },
+    VestingSuspended {
+        vault_id: u64,
+        timestamp: u64,
+    },
+    VestingResumed {
+        vault_id: u64,
+        extension: u64,
+    },
+    TokensStaked {
+        vault_id: u64,
+        amount: i128,
+    },
+    TokensUnstaked {
+        vault_id: u64,
+        amount: i128,
+    },
+    GrantInitialized {
+        recipient: String,
+        total_amount: U256,
+        start_time: u64,
+        end_time: u64,
+    },
+    GrantClaimed {
+        recipient: String,
+        amount: U256,
+        total_claimed: U256,
+    },
+    Other(String),
+}
+
+impl VestingEvent {
+    /// The vault this event belongs to, if any
+    pub fn vault_id(&self) -> Option<u64> {
+        use VestingEvent::*;
+        match self {
+            VaultCreated { vault_id, .. }
+            | VaultInitialized { vault_id }
+            | VaultTitleSet { vault_id, .. }
+            | IrrevocableMarked { vault_id }
+            | TokensClaimed { vault_id, .. }
+            | VaultTransferred { vault_id, .. }
+            | DelegateUpdated { vault_id, .. }
+            | VaultSplit { vault_id, .. }
+            | VaultsMerged { vault_id, .. }
+            | ScheduleShortened { vault_id, .. }
+            | VaultClawedBack { vault_id }
+            | RevocationPolicySet { vault_id, .. }
+            | VaultToppedUp { vault_id, .. }
+            | ScheduleAmended { vault_id, .. }
+            | VestingSuspended { vault_id, .. }
+            | VestingResumed { vault_id, .. }
+            | TokensStaked { vault_id, .. }
+            | TokensUnstaked { vault_id, .. } => Some(*vault_id),
+            GrantInitialized { .. } | GrantClaimed { .. } | Other(_) => None,
+        }
+    }
+}
+
+/// Decode a raw event against the `v1` schema.
+pub fn decode(raw: &RawEvent) -> Result<VestingEvent, Error> {
+    let name = match raw.topics.first() {
+        Some(ScVal::Symbol(name)) => name.0.to_utf8_string_lossy(),
+        _ => return Ok(VestingEvent::Other(String::new())),
+    };
+    match raw.topics.get(1) {
+        Some(ScVal::Symbol(version)) if version.0.as_slice() == SCHEMA_VERSION.as_bytes() => {}
+        Some(ScVal::Symbol(version)) if is_version(version.0.as_slice()) => {
+            return Err(Error::UnsupportedVersion {
+                event: name,
+                version: version.0.to_utf8_string_lossy(),
+            })
+        }
+        _ => return Ok(VestingEvent::Other(name)),
+    }
+
+    let f = Fields { raw, name: &name };
+    let event = match name.as_str() {
+        "vault_created" => VestingEvent::VaultCreated {
+            vault_id: f.topic(0, as_u64)?,
+            beneficiary: f.topic(1, as_address)?,
+            total_amount: f.field("total_amount", as_i128)?,
+            start_time: f.field("start_time", as_u64)?,
+            end_time: f.field("end_time", as_u64)?,
+            step_duration: f.field("step_duration", as_u64)?,
+            keeper_fee: f.field("keeper_fee", as_i128)?,
+            is_irrevocable: f.field("is_irrevocable", as_bool)?,
+            is_transferable: f.field("is_transferable", as_bool)?,
+            is_initialized: f.field("is_initialized", as_bool)?,
+            title: f.field("title", as_string)?,
+        },
+        "vault_initialized" => VestingEvent::VaultInitialized {
+            vault_id: f.topic(0, as_u64)?,
+        },
+        "vault_title_set" => VestingEvent::VaultTitleSet {
+            vault_id: f.topic(0, as_u64)?,
+            title: f.field("title", as_string)?,
+        },
+        "irrevocable_marked" => VestingEvent::IrrevocableMarked {
+            vault_id: f.topic(0, as_u64)?,
+        },
+        "tokens_claimed" => VestingEvent::TokensClaimed {
+            vault_id: f.topic(0, as_u64)?,
+            amount: f.field("amount", as_i128)?,
+            released_amount: f.field("released_amount", as_i128)?,
+        },
+        "vault_transferred" => VestingEvent::VaultTransferred {
+            vault_id: f.topic(0, as_u64)?,
+            to: f.field("to", as_address)?,
+            kind: f.field("kind", as_transfer_kind)?,
+        },
+        "delegate_updated" => VestingEvent::DelegateUpdated {
+            vault_id: f.topic(0, as_u64)?,
+            new_delegate: f.field("new_delegate", |v| as_option(v, as_address))?,
+        },
+        "vault_split" => VestingEvent::VaultSplit {
+            vault_id: f.topic(0, as_u64)?,
+            child_vault_id: f.field("child_vault_id", as_u64)?,
+            new_owner: f.field("new_owner", as_address)?,
+            amount: f.field("amount", as_i128)?,
+        },
+        "vaults_merged" => VestingEvent::VaultsMerged {
+            vault_id: f.topic(0, as_u64)?,
+            merged_vault_id: f.field("merged_vault_id", as_u64)?,
+        },
+        "schedule_shortened" => VestingEvent::ScheduleShortened {
+            vault_id: f.topic(0, as_u64)?,
+            total_amount: f.field("total_amount", as_i128)?,
+            end_time: f.field("end_time", as_u64)?,
+            step_duration: f.field("step_duration", as_u64)?,
+            suspended_at: f.field("suspended_at", |v| as_option(v, as_u64))?,
+        },
+        "vault_clawed_back" => VestingEvent::VaultClawedBack {
+            vault_id: f.topic(0, as_u64)?,
+        },
+        "revocation_policy_set" => VestingEvent::RevocationPolicySet {
+            vault_id: f.topic(0, as_u64)?,
+            policy: f.field("policy", as_revocation_policy)?,
+        },
+        "vault_topped_up" => VestingEvent::VaultToppedUp {
+            vault_id: f.topic(0, as_u64)?,
+            total_amount: f.field("total_amount", as_i128)?,
+            end_time: f.field("end_time", as_u64)?,
+        },
+        "schedule_amended" => VestingEvent::ScheduleAmended {
+            vault_id: f.topic(0, as_u64)?,
+            start_time: f.field("start_time", as_u64)?,
+            end_time: f.field("end_time", as_u64)?,
+            step_duration: f.field("step_duration", as_u64)?,
+        },
+        "vesting_suspended" => VestingEvent::VestingSuspended {
+            vault_id: f.topic(0, as_u64)?,
+            timestamp: f.field("timestamp", as_u64)?,
+        },
+        "vesting_resumed" => VestingEvent::VestingResumed {
+            vault_id: f.topic(0, as_u64)?,
+            extension: f.field("extension", as_u64)?,
+        },
+        "tokens_staked" => VestingEvent::TokensStaked {
+            vault_id: f.topic(0, as_u64)?,
+            amount: f.field("amount", as_i128)?,
+        },
+        "tokens_unstaked" => VestingEvent::TokensUnstaked {
+            vault_id: f.topic(0, as_u64)?,
+            amount: f.field("amount", as_i128)?,
+        },
+        "grant_initialized" => VestingEvent::GrantInitialized {
+            recipient: f.topic(0, as_address)?,
+            total_amount: f.field("total_amount", as_u256)?,
+            start_time: f.field("start_time", as_u64)?,
+            end_time: f.field("end_time", as_u64)?,
+        },
+        "grant_claimed" => VestingEvent::GrantClaimed {
+            recipient: f.topic(0, as_address)?,
+            amount: f.field("amount", as_u256)?,
+            total_claimed: f.field("total_claimed", as_u256)?,
+        },
+        _ => VestingEvent::Other(name.clone()),
+    };
+    Ok(event)
+}
+
+// "v" followed by digits
+fn is_version(symbol: &[u8]) -> bool {
+    symbol.len() > 1 && symbol[0] == b'v' && symbol[1..].iter().all(u8::is_ascii_digit)
+}
+
+// Topics and map fields of one versioned event
+struct Fields<'a> {
+    raw: &'a RawEvent,
+    name: &'a str,
+}
+
+impl Fields<'_> {
+    fn error(&self, message: String) -> Error {
+        Error::Decode {
+            event: format!("{} ({})", self.name, self.raw.id),
+            message,
+        }
+    }
+
+    // Indexed field `index`, counting after the name and version topics
+    fn topic<T>(&self, index: usize, convert: impl Fn(&ScVal) -> Option<T>) -> Result<T, Error> {
+        let value = self
+            .raw
+            .topics
+            .get(index + 2)
+            .ok_or_else(|| self.error(format!("missing topic {index}")))?;
+        convert(value).ok_or_else(|| self.error(format!("topic {index} has an unexpected type")))
+    }
+
+    fn field<T>(&self, key: &str, convert: impl Fn(&ScVal) -> Option<T>) -> Result<T, Error> {
+        let entries = match &self.raw.data {
+            ScVal::Map(Some(map)) => &map.0,
+            _ => return Err(self.error("data is not a map".into())),
+        };
+        let value = entries
+            .iter()
+            .find(
+                |entry| matches!(&entry.key, ScVal::Symbol(s) if s.0.as_slice() == key.as_bytes()),
+            )
+            .map(|entry| &entry.val)
+            .ok_or_else(|| self.error(format!("missing field {key}")))?;
+        convert(value).ok_or_else(|| self.error(format!("field {key} has an unexpected type")))
+    }
+}
+
+fn as_u64(value: &ScVal) -> Option<u64> {
+    match value {
+        ScVal::U64(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn as_bool(value: &ScVal) -> Option<bool> {
+    match value {
+        ScVal::Bool(b) => Some(*b),
+        _ => None,
+    }
+}
+
+fn as_i128(value: &ScVal) -> Option<i128> {
+    match value {
+        ScVal::I128(parts) => Some(((parts.hi as i128) << 64) | parts.lo as i128),
+        _ => None,
+    }
+}
+
+fn as_u256(value: &ScVal) -> Option<U256> {
+    match value {
+        ScVal::U256(parts) => {
+            let hi = ((parts.hi_hi as u128) << 64) | parts.hi_lo as u128;
+            let lo = ((parts.lo_hi as u128) << 64) | parts.lo_lo as u128;
+            Some(U256::from_words(hi, lo))
+        }
+        _ => None,
+    }
+}
+
+fn as_address(value: &ScVal) -> Option<String> {
+    match value {
+        ScVal::Address(address) => Some(address.to_string()),
+        _ => None,
+    }
+}
+
+fn as_string(value: &ScVal) -> Option<String> {
+    match value {
+        ScVal::String(s) => Some(s.0.to_utf8_string_lossy()),
+        _ => None,
+    }
+}
+
+fn as_option<T>(value: &ScVal, convert: impl Fn(&ScVal) -> Option<T>) -> Option<Option<T>> {
+    match value {
+        ScVal::Void => Some(None),
+        _ => convert(value).map(Some),
+    }
+}
+
+// A `#[contracttype]` enum value: [variant name, payload...]
+fn as_variant(value: &ScVal) -> Option<(String, &[ScVal])> {
+    match value {
+        ScVal::Vec(Some(items)) => match items.0.split_first() {
+            Some((ScVal::Symbol(variant), payload)) => {
+                Some((variant.0.to_utf8_string_lossy(), payload))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn as_transfer_kind(value: &ScVal) -> Option<TransferKind> {
+    match as_variant(value)? {
+        (variant, []) => match variant.as_str() {
+            "Admin" => Some(TransferKind::Admin),
+            "Owner" => Some(TransferKind::Owner),
+            "KeyRotation" => Some(TransferKind::KeyRotation),
+            "Recovery" => Some(TransferKind::Recovery),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn as_revocation_policy(value: &ScVal) -> Option<RevocationPolicy> {
+    match as_variant(value)? {
+        (variant, []) if variant == "ForfeitUnvested" => Some(RevocationPolicy::ForfeitUnvested),
+        (variant, [extension]) if variant == "LeaverTerms" => {
+            Some(RevocationPolicy::LeaverTerms(as_u64(extension)?))
+        }
+        _ => None,
+    }
+}
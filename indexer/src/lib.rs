@@ -0,0 +1,18 @@
+//! Off-chain indexer for `VestingContract` and `GrantContract`.
+//!
+//! Contract events are read from an [`EventSource`] (JSON-lines fixtures or
+//! anything that can page through RPC `getEvents`), decoded against the `v1`
+//! event schema and folded into a SQLite [`Store`]. The store keeps one row
+//! per vault with the same fields as the contract's `Vault`, so a replayed
+//! stream reproduces what `get_vault` returns on chain. Claims and
+//! revocations are also kept as dated [`Movement`]s for period reports.
+
+mod error;
+pub mod event;
+pub mod source;
+pub mod store;
+
+pub use error::Error;
+pub use event::{decode, RevocationPolicy, TransferKind, VestingEvent};
+pub use source::{EventSource, JsonLinesSource, RawEvent};
+pub use store::{GrantRecord, Movement, MovementKind, Store, VaultRecord};
@@ -0,0 +1,37 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::process::ExitCode;
+
+use vesting_indexer::{Error, JsonLinesSource, Store};
+
+const USAGE: &str = "usage: vesting-indexer <database.sqlite> <events.jsonl>...";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    match run(&args[0], &args[1..]) {
+        Ok(applied) => {
+            println!("applied {applied} new events");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Replay each fixture file into the database, in the order given
+fn run(database: &str, fixtures: &[String]) -> Result<usize, Error> {
+    let mut store = Store::open(database)?;
+    let mut applied = 0;
+    for path in fixtures {
+        let mut source = JsonLinesSource::new(BufReader::new(File::open(path)?));
+        applied += store.ingest(&mut source)?;
+    }
+    Ok(applied)
+}
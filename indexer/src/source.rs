@@ -0,0 +1,149 @@
+use std::io::BufRead;
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{
+    ContractEvent as XdrContractEvent, ContractEventBody, Limits, ReadXdr, ScAddress, ScVal,
+    WriteXdr,
+};
+
+use crate::Error;
+
+/// One contract event as delivered by a source, before decoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawEvent {
+    pub id: String, // Unique and ordered, e.g. RPC paging token "<ledger>-<index>"
+    pub ledger: u32,
+    pub timestamp: u64,      // Ledger close time in unix seconds
+    pub contract_id: String, // Strkey of the emitting contract ("C...")
+    pub topics: Vec<ScVal>,
+    pub data: ScVal,
+}
+
+impl RawEvent {
+    /// Wrap an XDR contract event, e.g. one taken from transaction meta.
+    pub fn from_xdr(
+        id: String,
+        ledger: u32,
+        timestamp: u64,
+        event: &XdrContractEvent,
+    ) -> Result<Self, Error> {
+        let contract_id = match &event.contract_id {
+            Some(contract_id) => ScAddress::Contract(contract_id.clone()).to_string(),
+            None => {
+                return Err(Error::Decode {
+                    event: id,
+                    message: "event has no contract id".into(),
+                })
+            }
+        };
+        let ContractEventBody::V0(body) = &event.body;
+        Ok(RawEvent {
+            id,
+            ledger,
+            timestamp,
+            contract_id,
+            topics: body.topics.to_vec(),
+            data: body.data.clone(),
+        })
+    }
+
+    /// Serialize as one fixture line (see [`JsonLinesSource`]).
+    pub fn to_json_line(&self) -> Result<String, Error> {
+        let line = FixtureLine {
+            id: self.id.clone(),
+            ledger: self.ledger,
+            timestamp: self.timestamp,
+            contract_id: self.contract_id.clone(),
+            topics: self
+                .topics
+                .iter()
+                .map(|topic| topic.to_xdr_base64(Limits::none()))
+                .collect::<Result<_, _>>()?,
+            data: self.data.to_xdr_base64(Limits::none())?,
+        };
+        Ok(serde_json::to_string(&line).expect("fixture line serializes"))
+    }
+}
+
+/// Anything that yields contract events in emission order.
+pub trait EventSource {
+    /// The next batch of events, oldest first. An empty batch means the
+    /// source has nothing more for now.
+    fn next_batch(&mut self) -> Result<Vec<RawEvent>, Error>;
+}
+
+// Fields mirror RPC `getEvents`, with the ledger close time as unix seconds
+// and topics/data as base64 XDR `ScVal`s
+#[derive(Serialize, Deserialize)]
+struct FixtureLine {
+    id: String,
+    ledger: u32,
+    timestamp: u64,
+    contract_id: String,
+    topics: Vec<String>,
+    data: String,
+}
+
+/// Reads events from JSON lines, one event per line:
+///
+/// ```text
+/// {"id":"0000000012-0000000001","ledger":12,"timestamp":1700000000,
+///  "contract_id":"CA...","topics":["AAAADw..."],"data":"AAAAEQ..."}
+/// ```
+///
+/// Blank lines are skipped.
+pub struct JsonLinesSource<R> {
+    reader: R,
+    line: usize,
+    batch_size: usize,
+}
+
+impl<R: BufRead> JsonLinesSource<R> {
+    pub fn new(reader: R) -> Self {
+        JsonLinesSource {
+            reader,
+            line: 0,
+            batch_size: 500,
+        }
+    }
+
+    fn parse(&self, text: &str) -> Result<RawEvent, Error> {
+        let fixture = |message: String| Error::Fixture {
+            line: self.line,
+            message,
+        };
+        let line: FixtureLine = serde_json::from_str(text).map_err(|e| fixture(e.to_string()))?;
+        let topics = line
+            .topics
+            .iter()
+            .map(|topic| ScVal::from_xdr_base64(topic, Limits::none()))
+            .collect::<Result<_, _>>()?;
+        Ok(RawEvent {
+            id: line.id,
+            ledger: line.ledger,
+            timestamp: line.timestamp,
+            contract_id: line.contract_id,
+            topics,
+            data: ScVal::from_xdr_base64(&line.data, Limits::none())?,
+        })
+    }
+}
+
+impl<R: BufRead> EventSource for JsonLinesSource<R> {
+    fn next_batch(&mut self) -> Result<Vec<RawEvent>, Error> {
+        let mut batch = Vec::new();
+        let mut text = String::new();
+        while batch.len() < self.batch_size {
+            text.clear();
+            if self.reader.read_line(&mut text)? == 0 {
+                break;
+            }
+            self.line += 1;
+            if text.trim().is_empty() {
+                continue;
+            }
+            batch.push(self.parse(text.trim())?);
+        }
+        Ok(batch)
+    }
+}
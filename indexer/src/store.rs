@@ -0,0 +1,567 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use ethnum::U256;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::event::{decode, RevocationPolicy, VestingEvent};
+use crate::{Error, EventSource, RawEvent};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS applied_events (
+    id TEXT PRIMARY KEY,
+    ledger INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS vaults (
+    contract_id TEXT NOT NULL,
+    vault_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    delegate TEXT,
+    total_amount TEXT NOT NULL,
+    released_amount TEXT NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    keeper_fee TEXT NOT NULL,
+    title TEXT NOT NULL,
+    is_initialized INTEGER NOT NULL,
+    is_irrevocable INTEGER NOT NULL,
+    creation_time INTEGER NOT NULL,
+    is_transferable INTEGER NOT NULL,
+    step_duration INTEGER NOT NULL,
+    staked_amount TEXT NOT NULL,
+    leaver_extension INTEGER,
+    suspended_at INTEGER,
+    suspended_duration INTEGER NOT NULL,
+    PRIMARY KEY (contract_id, vault_id)
+);
+
+CREATE INDEX IF NOT EXISTS vaults_by_owner ON vaults (contract_id, owner);
+
+CREATE TABLE IF NOT EXISTS movements (
+    contract_id TEXT NOT NULL,
+    vault_id INTEGER NOT NULL,
+    event_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    amount TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS movements_by_vault ON movements (contract_id, vault_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS grants (
+    contract_id TEXT PRIMARY KEY,
+    recipient TEXT NOT NULL,
+    total_amount TEXT NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    claimed TEXT NOT NULL
+);
+";
+
+const VAULT_COLUMNS: &str = "vault_id, owner, delegate, total_amount, released_amount, \
+    start_time, end_time, keeper_fee, title, is_initialized, is_irrevocable, creation_time, \
+    is_transferable, step_duration, staked_amount, leaver_extension, suspended_at, \
+    suspended_duration";
+
+/// Indexed copy of a contract `Vault`, field for field. Amounts are stored as
+/// decimal text since SQLite integers stop at 64 bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultRecord {
+    pub vault_id: u64,
+    pub owner: String,
+    pub delegate: Option<String>,
+    pub total_amount: i128,
+    pub released_amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub keeper_fee: i128,
+    pub title: String,
+    pub is_initialized: bool,
+    pub is_irrevocable: bool,
+    pub creation_time: u64,
+    pub is_transferable: bool,
+    pub step_duration: u64,
+    pub staked_amount: i128,
+    pub revocation_policy: RevocationPolicy,
+    pub suspended_at: Option<u64>,
+    pub suspended_duration: u64,
+}
+
+impl VaultRecord {
+    // Fold an event that only touches this vault into the record, mirroring
+    // what the contract wrote when it emitted the event
+    fn apply(&mut self, event: &VestingEvent) {
+        match event {
+            VestingEvent::VaultInitialized { .. } => self.is_initialized = true,
+            VestingEvent::VaultTitleSet { title, .. } => self.title = title.clone(),
+            VestingEvent::IrrevocableMarked { .. } => self.is_irrevocable = true,
+            VestingEvent::TokensClaimed {
+                released_amount, ..
+            } => self.released_amount = *released_amount,
+            VestingEvent::VaultTransferred { to, .. } => {
+                // Every transfer drops what the old owner granted, the delegate included
+                self.owner = to.clone();
+                self.delegate = None;
+            }
+            VestingEvent::DelegateUpdated { new_delegate, .. } => {
+                self.delegate = new_delegate.clone()
+            }
+            VestingEvent::ScheduleShortened {
+                total_amount,
+                end_time,
+                step_duration,
+                suspended_at,
+                ..
+            } => {
+                self.total_amount = *total_amount;
+                self.end_time = *end_time;
+                self.step_duration = *step_duration;
+                self.suspended_at = *suspended_at;
+            }
+            VestingEvent::VaultClawedBack { .. } => self.released_amount = self.total_amount,
+            VestingEvent::RevocationPolicySet { policy, .. } => self.revocation_policy = *policy,
+            VestingEvent::VaultToppedUp {
+                total_amount,
+                end_time,
+                ..
+            } => {
+                self.total_amount = *total_amount;
+                self.end_time = *end_time;
+            }
+            VestingEvent::ScheduleAmended {
+                start_time,
+                end_time,
+                step_duration,
+                ..
+            } => {
+                self.start_time = *start_time;
+                self.end_time = *end_time;
+                self.step_duration = *step_duration;
+                self.suspended_duration = 0;
+            }
+            VestingEvent::VestingSuspended { timestamp, .. } => {
+                self.suspended_at = Some(*timestamp)
+            }
+            VestingEvent::VestingResumed { extension, .. } => {
+                self.suspended_at = None;
+                self.suspended_duration += extension;
+                self.end_time += extension;
+            }
+            VestingEvent::TokensStaked { amount, .. } => self.staked_amount += amount,
+            VestingEvent::TokensUnstaked { amount, .. } => self.staked_amount -= amount,
+            _ => {}
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let leaver_extension: Option<u64> = row.get(15)?;
+        Ok(VaultRecord {
+            vault_id: row.get(0)?,
+            owner: row.get(1)?,
+            delegate: row.get(2)?,
+            total_amount: parse_column(row, 3)?,
+            released_amount: parse_column(row, 4)?,
+            start_time: row.get(5)?,
+            end_time: row.get(6)?,
+            keeper_fee: parse_column(row, 7)?,
+            title: row.get(8)?,
+            is_initialized: row.get(9)?,
+            is_irrevocable: row.get(10)?,
+            creation_time: row.get(11)?,
+            is_transferable: row.get(12)?,
+            step_duration: row.get(13)?,
+            staked_amount: parse_column(row, 14)?,
+            revocation_policy: match leaver_extension {
+                Some(extension) => RevocationPolicy::LeaverTerms(extension),
+                None => RevocationPolicy::ForfeitUnvested,
+            },
+            suspended_at: row.get(16)?,
+            suspended_duration: row.get(17)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementKind {
+    /// Tokens released to the beneficiary
+    Claim,
+    /// Unvested tokens taken back by revocation, termination or clawback
+    Revocation,
+}
+
+impl MovementKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MovementKind::Claim => "claim",
+            MovementKind::Revocation => "revocation",
+        }
+    }
+}
+
+/// A dated change in what a vault holds, kept so reports can split claims
+/// and revocations by period. The vault row only has the running totals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Movement {
+    pub vault_id: u64,
+    pub timestamp: u64,
+    pub kind: MovementKind,
+    pub amount: i128,
+}
+
+impl Movement {
+    // What `event` takes out of `vault`, read before the event is applied
+    fn of(vault: &VaultRecord, event: &VestingEvent, timestamp: u64) -> Option<Self> {
+        let (kind, amount) = match event {
+            VestingEvent::TokensClaimed { amount, .. } => (MovementKind::Claim, *amount),
+            VestingEvent::ScheduleShortened { total_amount, .. } => {
+                (MovementKind::Revocation, vault.total_amount - total_amount)
+            }
+            VestingEvent::VaultClawedBack { .. } => (
+                MovementKind::Revocation,
+                vault.total_amount - vault.released_amount,
+            ),
+            _ => return None,
+        };
+        // Acceleration shortens the schedule without taking anything back
+        (amount > 0).then_some(Movement {
+            vault_id: vault.vault_id,
+            timestamp,
+            kind,
+            amount,
+        })
+    }
+}
+
+/// Indexed state of a `GrantContract` instance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrantRecord {
+    pub recipient: String,
+    pub total_amount: U256,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub claimed: U256,
+}
+
+/// SQLite-backed materialized view of vesting and grant contracts.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Drain `source`, applying every event. Returns how many were new.
+    pub fn ingest(&mut self, source: &mut impl EventSource) -> Result<usize, Error> {
+        let mut applied = 0;
+        loop {
+            let batch = source.next_batch()?;
+            if batch.is_empty() {
+                return Ok(applied);
+            }
+            for event in &batch {
+                if self.apply(event)? {
+                    applied += 1;
+                }
+            }
+        }
+    }
+
+    /// Apply one event atomically. Events already applied (by id) are
+    /// skipped, so replaying an overlapping range is safe. Returns whether
+    /// the event was new.
+    pub fn apply(&mut self, raw: &RawEvent) -> Result<bool, Error> {
+        let event = decode(raw)?;
+
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO applied_events (id, ledger) VALUES (?1, ?2)",
+            params![raw.id, raw.ledger],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let contract_id = raw.contract_id.as_str();
+        match &event {
+            VestingEvent::VaultCreated {
+                vault_id,
+                beneficiary,
+                total_amount,
+                start_time,
+                end_time,
+                step_duration,
+                keeper_fee,
+                is_irrevocable,
+                is_transferable,
+                is_initialized,
+                title,
+            } => {
+                let vault = VaultRecord {
+                    vault_id: *vault_id,
+                    owner: beneficiary.clone(),
+                    delegate: None,
+                    total_amount: *total_amount,
+                    released_amount: 0,
+                    start_time: *start_time,
+                    end_time: *end_time,
+                    keeper_fee: *keeper_fee,
+                    title: title.clone(),
+                    is_initialized: *is_initialized,
+                    is_irrevocable: *is_irrevocable,
+                    creation_time: raw.timestamp,
+                    is_transferable: *is_transferable,
+                    step_duration: *step_duration,
+                    staked_amount: 0,
+                    revocation_policy: RevocationPolicy::ForfeitUnvested,
+                    suspended_at: None,
+                    suspended_duration: 0,
+                };
+                save_vault(&tx, contract_id, &vault)?;
+            }
+            VestingEvent::VaultSplit {
+                vault_id,
+                child_vault_id,
+                amount,
+                ..
+            } => {
+                // Same proportional split of claimed tokens as split_vault; the
+                // child stays with the parent's owner until a transfer is accepted
+                let mut parent = require_vault(&tx, contract_id, *vault_id, &event)?;
+                let child_released = parent.released_amount * amount / parent.total_amount;
+                let child = VaultRecord {
+                    vault_id: *child_vault_id,
+                    delegate: None,
+                    total_amount: *amount,
+                    released_amount: child_released,
+                    ..parent.clone()
+                };
+                parent.total_amount -= amount;
+                parent.released_amount -= child_released;
+                save_vault(&tx, contract_id, &parent)?;
+                save_vault(&tx, contract_id, &child)?;
+            }
+            VestingEvent::VaultsMerged {
+                vault_id,
+                merged_vault_id,
+            } => {
+                let mut vault = require_vault(&tx, contract_id, *vault_id, &event)?;
+                let merged = require_vault(&tx, contract_id, *merged_vault_id, &event)?;
+                vault.total_amount += merged.total_amount;
+                vault.released_amount += merged.released_amount;
+                save_vault(&tx, contract_id, &vault)?;
+                tx.execute(
+                    "DELETE FROM vaults WHERE contract_id = ?1 AND vault_id = ?2",
+                    params![contract_id, merged_vault_id],
+                )?;
+            }
+            VestingEvent::GrantInitialized {
+                recipient,
+                total_amount,
+                start_time,
+                end_time,
+            } => {
+                tx.execute(
+                    "INSERT OR REPLACE INTO grants
+                        (contract_id, recipient, total_amount, start_time, end_time, claimed)
+                     VALUES (?1, ?2, ?3, ?4, ?5, '0')",
+                    params![
+                        contract_id,
+                        recipient,
+                        total_amount.to_string(),
+                        start_time,
+                        end_time
+                    ],
+                )?;
+            }
+            VestingEvent::GrantClaimed { total_claimed, .. } => {
+                tx.execute(
+                    "UPDATE grants SET claimed = ?2 WHERE contract_id = ?1",
+                    params![contract_id, total_claimed.to_string()],
+                )?;
+            }
+            VestingEvent::Other(_) => {}
+            _ => {
+                let vault_id = event.vault_id().expect("vault event");
+                let mut vault = require_vault(&tx, contract_id, vault_id, &event)?;
+                if let Some(movement) = Movement::of(&vault, &event, raw.timestamp) {
+                    tx.execute(
+                        "INSERT INTO movements
+                            (contract_id, vault_id, event_id, timestamp, kind, amount)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            contract_id,
+                            vault_id,
+                            raw.id,
+                            movement.timestamp,
+                            movement.kind.as_str(),
+                            movement.amount.to_string()
+                        ],
+                    )?;
+                }
+                vault.apply(&event);
+                save_vault(&tx, contract_id, &vault)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn vault(&self, contract_id: &str, vault_id: u64) -> Result<Option<VaultRecord>, Error> {
+        load_vault(&self.conn, contract_id, vault_id)
+    }
+
+    /// Every vault of `contract_id`, by id
+    pub fn vaults(&self, contract_id: &str) -> Result<Vec<VaultRecord>, Error> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults WHERE contract_id = ?1 ORDER BY vault_id"
+        ))?;
+        let vaults = statement
+            .query_map(params![contract_id], VaultRecord::from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(vaults)
+    }
+
+    /// Vaults currently owned by `owner`, by id
+    pub fn vaults_of(&self, contract_id: &str, owner: &str) -> Result<Vec<VaultRecord>, Error> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults
+             WHERE contract_id = ?1 AND owner = ?2 ORDER BY vault_id"
+        ))?;
+        let vaults = statement
+            .query_map(params![contract_id, owner], VaultRecord::from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(vaults)
+    }
+
+    /// Claims and revocations of a vault, oldest first
+    pub fn movements(&self, contract_id: &str, vault_id: u64) -> Result<Vec<Movement>, Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT timestamp, kind, amount FROM movements
+             WHERE contract_id = ?1 AND vault_id = ?2 ORDER BY timestamp, event_id",
+        )?;
+        let movements = statement
+            .query_map(params![contract_id, vault_id], |row| {
+                let kind: String = row.get(1)?;
+                Ok(Movement {
+                    vault_id,
+                    timestamp: row.get(0)?,
+                    kind: match kind.as_str() {
+                        "claim" => MovementKind::Claim,
+                        _ => MovementKind::Revocation,
+                    },
+                    amount: parse_column(row, 2)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(movements)
+    }
+
+    pub fn grant(&self, contract_id: &str) -> Result<Option<GrantRecord>, Error> {
+        let grant = self
+            .conn
+            .query_row(
+                "SELECT recipient, total_amount, start_time, end_time, claimed
+                 FROM grants WHERE contract_id = ?1",
+                params![contract_id],
+                |row| {
+                    Ok(GrantRecord {
+                        recipient: row.get(0)?,
+                        total_amount: parse_column(row, 1)?,
+                        start_time: row.get(2)?,
+                        end_time: row.get(3)?,
+                        claimed: parse_column(row, 4)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(grant)
+    }
+}
+
+fn load_vault(
+    conn: &Connection,
+    contract_id: &str,
+    vault_id: u64,
+) -> Result<Option<VaultRecord>, Error> {
+    let vault = conn
+        .query_row(
+            &format!("SELECT {VAULT_COLUMNS} FROM vaults WHERE contract_id = ?1 AND vault_id = ?2"),
+            params![contract_id, vault_id],
+            VaultRecord::from_row,
+        )
+        .optional()?;
+    Ok(vault)
+}
+
+fn require_vault(
+    conn: &Connection,
+    contract_id: &str,
+    vault_id: u64,
+    event: &VestingEvent,
+) -> Result<VaultRecord, Error> {
+    load_vault(conn, contract_id, vault_id)?.ok_or_else(|| Error::UnknownVault {
+        event: format!("{event:?}"),
+        vault_id,
+    })
+}
+
+fn save_vault(conn: &Connection, contract_id: &str, vault: &VaultRecord) -> Result<(), Error> {
+    let leaver_extension = match vault.revocation_policy {
+        RevocationPolicy::ForfeitUnvested => None,
+        RevocationPolicy::LeaverTerms(extension) => Some(extension),
+    };
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO vaults (contract_id, {VAULT_COLUMNS})
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                     ?17, ?18, ?19)"
+        ),
+        params![
+            contract_id,
+            vault.vault_id,
+            vault.owner,
+            vault.delegate,
+            vault.total_amount.to_string(),
+            vault.released_amount.to_string(),
+            vault.start_time,
+            vault.end_time,
+            vault.keeper_fee.to_string(),
+            vault.title,
+            vault.is_initialized,
+            vault.is_irrevocable,
+            vault.creation_time,
+            vault.is_transferable,
+            vault.step_duration,
+            vault.staked_amount.to_string(),
+            leaver_extension,
+            vault.suspended_at,
+            vault.suspended_duration,
+        ],
+    )?;
+    Ok(())
+}
+
+// Amounts wider than 64 bits live in TEXT columns
+fn parse_column<T>(row: &Row, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let text: String = row.get(index)?;
+    text.parse()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
@@ -0,0 +1,344 @@
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::xdr::ScAddress;
+use grant_contracts::{GrantClaimed, GrantInitialized};
+use soroban_sdk::{contract, contractimpl, vec, Address, Env, Event, String, U256};
+use vesting_contracts::{
+    BatchCreateData, RevocationPolicy, StakingAdapter, TerminationReason, Vault, VestingContract,
+    VestingContractClient,
+};
+use vesting_indexer::{
+    GrantRecord, JsonLinesSource, Movement, MovementKind, RawEvent,
+    RevocationPolicy as IndexedPolicy, Store, VaultRecord,
+};
+
+#[contract]
+pub struct MockStakingContract;
+
+#[contractimpl]
+impl StakingAdapter for MockStakingContract {
+    fn stake(_env: Env, _vault_id: u64, _amount: i128, _validator: Address) {}
+    fn unstake(_env: Env, _vault_id: u64, _amount: i128, _validator: Address) {}
+    fn balance_of(_env: Env, _vault_id: u64) -> i128 {
+        0
+    }
+    fn pending_rewards(_env: Env, _vault_id: u64) -> i128 {
+        0
+    }
+}
+
+// Collects the vesting contract's events after every call. The test
+// environment only keeps the last invocation's events, and each call gets its
+// own ledger so event ids stay unique.
+struct Recorder<'a> {
+    env: &'a Env,
+    contract_id: Address,
+    events: Vec<RawEvent>,
+}
+
+impl Recorder<'_> {
+    fn record(&mut self) {
+        let ledger = self.env.ledger().sequence();
+        let timestamp = self.env.ledger().timestamp();
+        let all = self
+            .env
+            .events()
+            .all()
+            .filter_by_contract(&self.contract_id);
+        for (index, event) in all.events().iter().enumerate() {
+            let id = format!("{ledger:010}-{index:010}");
+            self.events
+                .push(RawEvent::from_xdr(id, ledger, timestamp, event).unwrap());
+        }
+        self.env.ledger().with_mut(|li| li.sequence_number += 1);
+    }
+}
+
+fn strkey(address: &Address) -> std::string::String {
+    ScAddress::from(address).to_string()
+}
+
+fn record_of(vault_id: u64, vault: Vault) -> VaultRecord {
+    VaultRecord {
+        vault_id,
+        owner: strkey(&vault.owner),
+        delegate: vault.delegate.as_ref().map(strkey),
+        total_amount: vault.total_amount,
+        released_amount: vault.released_amount,
+        start_time: vault.start_time,
+        end_time: vault.end_time,
+        keeper_fee: vault.keeper_fee,
+        title: vault.title.to_string(),
+        is_initialized: vault.is_initialized,
+        is_irrevocable: vault.is_irrevocable,
+        creation_time: vault.creation_time,
+        is_transferable: vault.is_transferable,
+        step_duration: vault.step_duration,
+        staked_amount: vault.staked_amount,
+        revocation_policy: match vault.revocation_policy {
+            RevocationPolicy::ForfeitUnvested => IndexedPolicy::ForfeitUnvested,
+            RevocationPolicy::LeaverTerms(extension) => IndexedPolicy::LeaverTerms(extension),
+        },
+        suspended_at: vault.suspended_at,
+        suspended_duration: vault.suspended_duration,
+    }
+}
+
+// Every vault the contract knows about must match the indexed row exactly,
+// and merged-away vaults must be gone from both
+fn assert_rebuilt(store: &Store, client: &VestingContractClient, contract: &str, count: u64) {
+    for vault_id in 1..=count {
+        let indexed = store.vault(contract, vault_id).unwrap();
+        match client.try_get_vault(&vault_id) {
+            Ok(Ok(vault)) => assert_eq!(indexed, Some(record_of(vault_id, vault))),
+            _ => assert_eq!(indexed, None, "vault {vault_id}"),
+        }
+    }
+}
+
+fn run_scenario<'a>(env: &'a Env) -> (Address, VestingContractClient<'a>, Vec<RawEvent>, u64) {
+    env.mock_all_auths();
+    let contract_id = env.register(VestingContract, ());
+    let client = VestingContractClient::new(env, &contract_id);
+    let mut recorder = Recorder {
+        env,
+        contract_id: contract_id.clone(),
+        events: Vec::new(),
+    };
+
+    // The contract is its own admin and the owner of the vaults it claims from
+    client.initialize(&contract_id, &1_000_000i128);
+    recorder.record();
+    let staking_contract_id = env.register(MockStakingContract, ());
+    client.set_staking_contract(&staking_contract_id);
+    recorder.record();
+
+    let alice = Address::generate(env);
+    let bob = Address::generate(env);
+    let validator = Address::generate(env);
+    let now = env.ledger().timestamp();
+
+    let linear = client.create_vault_full(
+        &contract_id,
+        &1000i128,
+        &now,
+        &(now + 1000),
+        &5i128,
+        &true,
+        &true,
+        &0u64,
+    );
+    recorder.record();
+    let stepped = client.create_vault_full(
+        &contract_id,
+        &2000i128,
+        &now,
+        &(now + 2000),
+        &0i128,
+        &true,
+        &false,
+        &100u64,
+    );
+    recorder.record();
+    let lazy = client.batch_create_vaults_lazy(&BatchCreateData {
+        recipients: vec![env, alice.clone()],
+        amounts: vec![env, 500i128],
+        start_times: vec![env, now],
+        end_times: vec![env, now + 500],
+        keeper_fees: vec![env, 0i128],
+        step_durations: vec![env, 0u64],
+    });
+    recorder.record();
+    let merge_into = client.create_vault_full(
+        &contract_id,
+        &300i128,
+        &now,
+        &(now + 3000),
+        &0i128,
+        &true,
+        &true,
+        &0u64,
+    );
+    recorder.record();
+    let merge_from = client.create_vault_full(
+        &contract_id,
+        &200i128,
+        &now,
+        &(now + 3000),
+        &0i128,
+        &true,
+        &true,
+        &0u64,
+    );
+    recorder.record();
+
+    client.set_vault_title(&linear, &String::from_str(env, "Founder grant"));
+    recorder.record();
+    client.set_delegate(&linear, &Some(alice.clone()));
+    recorder.record();
+    client.mark_irrevocable(&lazy.get(0).unwrap());
+    recorder.record();
+    client.initialize_vaults(&vec![env, lazy.get(0).unwrap()]);
+    recorder.record();
+    client.set_revocation_policy(&linear, &RevocationPolicy::LeaverTerms(100));
+    recorder.record();
+
+    env.ledger().with_mut(|li| li.timestamp = now + 300);
+    client.claim_tokens(&linear, &100i128);
+    recorder.record();
+    client.stake_tokens(&stepped, &contract_id, &400i128, &validator);
+    recorder.record();
+    client.unstake_tokens(&stepped, &contract_id, &150i128, &validator);
+    recorder.record();
+    client.revoke_partial(&stepped, &200i128);
+    recorder.record();
+    client.top_up_vault(&stepped, &100i128, &Some(now + 2500));
+    recorder.record();
+    client.suspend_vesting(&linear);
+    recorder.record();
+
+    env.ledger().with_mut(|li| li.timestamp = now + 400);
+    client.resume_vesting(&linear);
+    recorder.record();
+    let child = client.split_vault(&linear, &400i128, &bob);
+    recorder.record();
+    client.accept_vault_transfer(&child);
+    recorder.record();
+    // The delegate goes with the old owner, even on an admin transfer
+    client.set_delegate(&stepped, &Some(bob.clone()));
+    recorder.record();
+    client.transfer_beneficiary(&stepped, &alice);
+    recorder.record();
+    client.propose_amendment(&stepped, &now, &(now + 3000), &0u64);
+    recorder.record();
+    client.amend_schedule(&stepped);
+    recorder.record();
+    client.merge_vaults(&merge_into, &merge_from);
+    recorder.record();
+
+    env.ledger().with_mut(|li| li.timestamp = now + 600);
+    client.terminate(&linear, &TerminationReason::Retirement, &(now + 600));
+    recorder.record();
+    client.terminate(&child, &TerminationReason::BadLeaver, &(now + 600));
+    recorder.record();
+    let clawed = client.create_vault_full(
+        &contract_id,
+        &50i128,
+        &now,
+        &(now + 1000),
+        &0i128,
+        &true,
+        &false,
+        &0u64,
+    );
+    recorder.record();
+    client.clawback_vault(&clawed);
+    recorder.record();
+
+    (contract_id, client, recorder.events, clawed)
+}
+
+#[test]
+fn test_replay_rebuilds_every_vault() {
+    let env = Env::default();
+    let (contract_id, client, events, vault_count) = run_scenario(&env);
+
+    let mut store = Store::open_in_memory().unwrap();
+    for event in &events {
+        assert!(store.apply(event).unwrap());
+    }
+    let contract = strkey(&contract_id);
+    assert_rebuilt(&store, &client, &contract, vault_count);
+
+    // Replaying the same events again changes nothing
+    for event in &events {
+        assert!(!store.apply(event).unwrap());
+    }
+    assert_rebuilt(&store, &client, &contract, vault_count);
+    assert_eq!(store.vaults_of(&contract, &contract).unwrap().len(), 3);
+
+    // Claims and revocations are kept with their timestamps
+    let now = env.ledger().timestamp() - 600;
+    let linear = store.movements(&contract, 1).unwrap();
+    assert_eq!(
+        linear[0],
+        Movement {
+            vault_id: 1,
+            timestamp: now + 300,
+            kind: MovementKind::Claim,
+            amount: 100,
+        }
+    );
+    // Retiring under leaver terms forfeits nothing
+    assert_eq!(linear.len(), 1);
+    assert!(store.movements(&contract, 2).unwrap().contains(&Movement {
+        vault_id: 2,
+        timestamp: now + 300,
+        kind: MovementKind::Revocation,
+        amount: 200,
+    }));
+    assert_eq!(
+        store.movements(&contract, vault_count).unwrap(),
+        [Movement {
+            vault_id: vault_count,
+            timestamp: now + 600,
+            kind: MovementKind::Revocation,
+            amount: 50,
+        }]
+    );
+}
+
+#[test]
+fn test_json_fixture_round_trip() {
+    let env = Env::default();
+    let (contract_id, client, events, vault_count) = run_scenario(&env);
+
+    let fixture: std::string::String = events
+        .iter()
+        .map(|event| event.to_json_line().unwrap() + "\n\n")
+        .collect();
+    let mut source = JsonLinesSource::new(fixture.as_bytes());
+    let mut store = Store::open_in_memory().unwrap();
+    assert_eq!(store.ingest(&mut source).unwrap(), events.len());
+
+    assert_rebuilt(&store, &client, &strkey(&contract_id), vault_count);
+}
+
+#[test]
+fn test_grant_events() {
+    let env = Env::default();
+    let grant_id = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let total_amount = U256::from_u128(&env, 1_000_000);
+
+    let mut store = Store::open_in_memory().unwrap();
+    let initialized = GrantInitialized {
+        recipient: recipient.clone(),
+        total_amount: total_amount.clone(),
+        start_time: 100,
+        end_time: 1100,
+    }
+    .to_xdr(&env, &grant_id);
+    store
+        .apply(&RawEvent::from_xdr("1-0".into(), 1, 100, &initialized).unwrap())
+        .unwrap();
+    let claimed = GrantClaimed {
+        recipient: recipient.clone(),
+        amount: U256::from_u128(&env, 400_000),
+        total_claimed: U256::from_u128(&env, 400_000),
+    }
+    .to_xdr(&env, &grant_id);
+    store
+        .apply(&RawEvent::from_xdr("2-0".into(), 2, 500, &claimed).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        store.grant(&strkey(&grant_id)).unwrap(),
+        Some(GrantRecord {
+            recipient: strkey(&recipient),
+            total_amount: 1_000_000u128.into(),
+            start_time: 100,
+            end_time: 1100,
+            claimed: 400_000u128.into(),
+        })
+    );
+}